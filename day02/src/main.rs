@@ -1,48 +1,42 @@
 mod parser;
+mod planner;
+mod submarine;
+mod trajectory;
 
-struct Submarine {
-    depth: i64,
-    distance: i64,
-    aim: i64,
-}
+use submarine::Motion;
 
-fn coarse_calculation(cmds: &Vec<parser::Command>) -> i64 {
-    let mut s = Submarine {
-        depth: 0,
-        distance: 0,
-        aim: 0,
-    };
+fn coarse_calculation(cmds: &[parser::Command]) -> i64 {
+    let end = *trajectory::record(cmds, Motion::Coarse).last();
+    end.depth * end.distance
+}
 
-    cmds.iter().for_each(|cmd| match cmd {
-        parser::Command::Up(d) => s.depth -= d,
-        parser::Command::Down(d) => s.depth += d,
-        parser::Command::Forward(d) => s.distance += d,
-    });
+fn accurate_calculation(cmds: &[parser::Command]) -> i64 {
+    let end = *trajectory::record(cmds, Motion::Accurate).last();
+    end.depth * end.distance
+}
 
-    s.depth * s.distance
+fn export_trajectories(cmds: &[parser::Command]) {
+    for (motion, name) in [(Motion::Coarse, "coarse"), (Motion::Accurate, "accurate")] {
+        let t = trajectory::record(cmds, motion);
+        std::fs::write(format!("{}.csv", name), t.to_csv()).expect("Unable to write CSV");
+        std::fs::write(format!("{}.svg", name), t.to_svg()).expect("Unable to write SVG");
+        println!("Trajectory written to {}.csv and {}.svg", name, name);
+    }
 }
 
-fn accurate_calculation(cmds: &Vec<parser::Command>) -> i64 {
-    let mut s = Submarine {
-        depth: 0,
-        distance: 0,
-        aim: 0,
-    };
-
-    cmds.iter().for_each(|cmd| match cmd {
-        parser::Command::Up(x) => {
-            s.aim -= *x;
-        }
-        parser::Command::Down(x) => {
-            s.aim += *x;
-        }
-        parser::Command::Forward(x) => {
-            s.depth = s.depth + (s.aim * x);
-            s.distance += x;
-        }
-    });
-
-    s.depth * s.distance
+fn print_plans(cmds: &[parser::Command]) {
+    for motion in [Motion::Coarse, Motion::Accurate] {
+        let end = *trajectory::record(cmds, motion).last();
+        let target = planner::Target {
+            distance: end.distance,
+            depth: end.depth,
+        };
+        let route = planner::plan(target, motion).expect("Unable to plan route");
+        println!(
+            "{:?} route to ({}, {}): {:?}",
+            motion, target.distance, target.depth, route
+        );
+    }
 }
 
 fn main() {
@@ -50,4 +44,12 @@ fn main() {
     let result = parser::parse(&content).expect("Unable to parse file");
     println!("Coarse movement: {}", coarse_calculation(&result));
     println!("Accurate movement: {}", accurate_calculation(&result));
+
+    if std::env::args().any(|arg| arg == "--export") {
+        export_trajectories(&result);
+    }
+
+    if std::env::args().any(|arg| arg == "--plan") {
+        print_plans(&result);
+    }
 }
@@ -0,0 +1,220 @@
+use crate::parser::Command;
+use crate::submarine::Motion;
+use crate::trajectory::{self, Waypoint};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Target {
+    pub distance: i64,
+    pub depth: i64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    // the submarine can't move backwards
+    NegativeDistance(i64),
+    // the aim model can't change depth without moving forward
+    DepthWithoutDistance(i64),
+    // going up by i64::MIN doesn't fit in a command
+    DepthOutOfRange(i64),
+    // the synthesized commands don't land on the target
+    Mismatch(Waypoint),
+}
+
+pub fn plan(target: Target, motion: Motion) -> Result<Vec<Command>, PlanError> {
+    if target.distance < 0 {
+        return Err(PlanError::NegativeDistance(target.distance));
+    }
+    if target.depth == i64::MIN {
+        return Err(PlanError::DepthOutOfRange(target.depth));
+    }
+
+    let cmds = match motion {
+        Motion::Coarse => coarse_plan(target),
+        Motion::Accurate => accurate_plan(target)?,
+    };
+
+    validate(&cmds, target, motion)?;
+    Ok(cmds)
+}
+
+pub fn validate(cmds: &[Command], target: Target, motion: Motion) -> Result<(), PlanError> {
+    let end = *trajectory::record(cmds, motion).last();
+    if end.distance == target.distance && end.depth == target.depth {
+        Ok(())
+    } else {
+        Err(PlanError::Mismatch(end))
+    }
+}
+
+fn vertical(amount: i64) -> Option<Command> {
+    match amount {
+        0 => None,
+        a if a > 0 => Some(Command::Down(a)),
+        a => Some(Command::Up(-a)),
+    }
+}
+
+fn forward(amount: i64) -> Option<Command> {
+    if amount > 0 {
+        Some(Command::Forward(amount))
+    } else {
+        None
+    }
+}
+
+// one command per axis, skipping the ones that would be zero
+fn coarse_plan(target: Target) -> Vec<Command> {
+    forward(target.distance)
+        .into_iter()
+        .chain(vertical(target.depth))
+        .collect()
+}
+
+// depth is the sum of aim * forward, so:
+// - if the distance divides the depth, aim once and move forward once
+// - otherwise move forward with no aim, then aim and cover the last stretch,
+//   which must be a divisor of the depth (1 always is)
+fn accurate_plan(target: Target) -> Result<Vec<Command>, PlanError> {
+    let Target { distance, depth } = target;
+
+    if depth == 0 {
+        return Ok(forward(distance).into_iter().collect());
+    }
+
+    if distance == 0 {
+        return Err(PlanError::DepthWithoutDistance(depth));
+    }
+
+    if depth % distance == 0 {
+        return Ok(vertical(depth / distance)
+            .into_iter()
+            .chain(forward(distance))
+            .collect());
+    }
+
+    let last_stretch = largest_divisor_below(depth.unsigned_abs(), distance);
+    Ok(forward(distance - last_stretch)
+        .into_iter()
+        .chain(vertical(depth / last_stretch))
+        .chain(forward(last_stretch))
+        .collect())
+}
+
+// the limit is a positive distance
+fn largest_divisor_below(n: u64, limit: i64) -> i64 {
+    let limit = limit as u64;
+    let largest = if limit <= n / limit {
+        // below sqrt(n), the first divisor counting down from the limit is the answer
+        (1..limit).rev().find(|&d| n.is_multiple_of(d))
+    } else {
+        // otherwise each divisor up to sqrt(n) gives its pair too
+        (1..)
+            .take_while(|&i| i <= n / i)
+            .filter(|&i| n.is_multiple_of(i))
+            .flat_map(|i| [i, n / i])
+            .filter(|&d| d < limit)
+            .max()
+    };
+    largest.map_or(1, |d| d as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Command::*;
+    use super::*;
+
+    fn target(distance: i64, depth: i64) -> Target {
+        Target { distance, depth }
+    }
+
+    #[test]
+    fn test_coarse_plan() {
+        assert_eq!(
+            plan(target(15, 10), Motion::Coarse),
+            Ok(vec![Forward(15), Down(10)])
+        );
+        assert_eq!(plan(target(0, -3), Motion::Coarse), Ok(vec![Up(3)]));
+        assert_eq!(plan(target(0, 0), Motion::Coarse), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_accurate_plan_divisible() {
+        assert_eq!(
+            plan(target(15, 60), Motion::Accurate),
+            Ok(vec![Down(4), Forward(15)])
+        );
+        assert_eq!(plan(target(7, 0), Motion::Accurate), Ok(vec![Forward(7)]));
+    }
+
+    #[test]
+    fn test_accurate_plan_not_divisible() {
+        assert_eq!(
+            plan(target(10, 21), Motion::Accurate),
+            Ok(vec![Forward(3), Down(3), Forward(7)])
+        );
+        assert_eq!(
+            plan(target(4, -7), Motion::Accurate),
+            Ok(vec![Forward(3), Up(7), Forward(1)])
+        );
+    }
+
+    #[test]
+    fn test_unreachable_targets() {
+        assert_eq!(
+            plan(target(-1, 0), Motion::Coarse),
+            Err(PlanError::NegativeDistance(-1))
+        );
+        assert_eq!(
+            plan(target(0, 5), Motion::Accurate),
+            Err(PlanError::DepthWithoutDistance(5))
+        );
+        assert_eq!(
+            plan(target(3, i64::MIN), Motion::Accurate),
+            Err(PlanError::DepthOutOfRange(i64::MIN))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let cmds = vec![Forward(5), Down(5), Forward(8)];
+        assert_eq!(validate(&cmds, target(13, 40), Motion::Accurate), Ok(()));
+        assert!(matches!(
+            validate(&cmds, target(13, 40), Motion::Coarse),
+            Err(PlanError::Mismatch(Waypoint {
+                distance: 13,
+                depth: 5,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_largest_divisor_below() {
+        assert_eq!(largest_divisor_below(36, 5), 4);
+        assert_eq!(largest_divisor_below(36, 10), 9);
+        assert_eq!(largest_divisor_below(36, 36), 18);
+        assert_eq!(largest_divisor_below(7, 3), 1);
+        // a short distance doesn't need to look for the divisors of the whole depth
+        assert_eq!(largest_divisor_below(i64::MAX as u64, 10), 7);
+        assert_eq!(largest_divisor_below(u64::MAX, 4), 3);
+        assert_eq!(
+            plan(target(10, i64::MAX), Motion::Accurate).unwrap().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_plan_round_trip() {
+        for distance in 0..20 {
+            for depth in -30..30 {
+                let t = target(distance, depth);
+                let coarse = plan(t, Motion::Coarse).unwrap();
+                assert!(coarse.len() <= 2);
+                if distance > 0 || depth == 0 {
+                    let accurate = plan(t, Motion::Accurate).unwrap();
+                    assert!(accurate.len() <= 3);
+                }
+            }
+        }
+    }
+}
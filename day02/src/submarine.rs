@@ -0,0 +1,70 @@
+use crate::parser::Command;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Submarine {
+    pub depth: i64,
+    pub distance: i64,
+    pub aim: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motion {
+    // up and down change the depth directly
+    Coarse,
+    // up and down change the aim, forward moves along it
+    Accurate,
+}
+
+impl Submarine {
+    pub fn apply(&mut self, cmd: &Command, motion: Motion) {
+        match (motion, cmd) {
+            (Motion::Coarse, Command::Up(d)) => self.depth -= d,
+            (Motion::Coarse, Command::Down(d)) => self.depth += d,
+            (Motion::Coarse, Command::Forward(d)) => self.distance += d,
+            (Motion::Accurate, Command::Up(x)) => self.aim -= x,
+            (Motion::Accurate, Command::Down(x)) => self.aim += x,
+            (Motion::Accurate, Command::Forward(x)) => {
+                self.depth += self.aim * x;
+                self.distance += x;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Command::*;
+
+    #[test]
+    fn test_coarse_apply() {
+        let mut s = Submarine::default();
+        s.apply(&Forward(5), Motion::Coarse);
+        s.apply(&Down(5), Motion::Coarse);
+        s.apply(&Up(2), Motion::Coarse);
+        assert_eq!(
+            s,
+            Submarine {
+                depth: 3,
+                distance: 5,
+                aim: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_accurate_apply() {
+        let mut s = Submarine::default();
+        s.apply(&Forward(5), Motion::Accurate);
+        s.apply(&Down(5), Motion::Accurate);
+        s.apply(&Forward(8), Motion::Accurate);
+        assert_eq!(
+            s,
+            Submarine {
+                depth: 40,
+                distance: 13,
+                aim: 5
+            }
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::parser::Command;
+use crate::submarine::{Motion, Submarine};
+
+const SVG_WIDTH: f64 = 800.0;
+const SVG_HEIGHT: f64 = 400.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Waypoint {
+    pub step: usize,
+    pub distance: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+pub struct Trajectory {
+    pub motion: Motion,
+    pub waypoints: Vec<Waypoint>,
+}
+
+pub fn record(cmds: &[Command], motion: Motion) -> Trajectory {
+    let mut s = Submarine::default();
+    let mut waypoints = vec![Waypoint::at(0, &s)];
+
+    cmds.iter().enumerate().for_each(|(idx, cmd)| {
+        s.apply(cmd, motion);
+        waypoints.push(Waypoint::at(idx + 1, &s));
+    });
+
+    Trajectory { motion, waypoints }
+}
+
+impl Waypoint {
+    fn at(step: usize, s: &Submarine) -> Waypoint {
+        Waypoint {
+            step,
+            distance: s.distance,
+            depth: s.depth,
+            aim: s.aim,
+        }
+    }
+}
+
+impl Trajectory {
+    pub fn last(&self) -> &Waypoint {
+        // the starting point is always recorded, so there is at least one waypoint
+        self.waypoints.last().unwrap()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("step,distance,depth,aim\n");
+        self.waypoints.iter().for_each(|w| {
+            output.push_str(&format!(
+                "{},{},{},{}\n",
+                w.step, w.distance, w.depth, w.aim
+            ));
+        });
+        output
+    }
+
+    // depth profile: distance on the x axis, depth growing downwards like SVG's y axis
+    pub fn to_svg(&self) -> String {
+        let (min_depth, max_depth, max_distance) =
+            self.waypoints
+                .iter()
+                .fold((0, 0, 0), |(min_d, max_d, max_x), w| {
+                    (
+                        min_d.min(w.depth),
+                        max_d.max(w.depth),
+                        max_x.max(w.distance),
+                    )
+                });
+
+        let x_scale = SVG_WIDTH / (max_distance.max(1) as f64);
+        let y_scale = SVG_HEIGHT / ((max_depth - min_depth).max(1) as f64);
+
+        let points = self
+            .waypoints
+            .iter()
+            .map(|w| {
+                format!(
+                    "{:.2},{:.2}",
+                    w.distance as f64 * x_scale,
+                    (w.depth - min_depth) as f64 * y_scale
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\">\n\
+             <title>{:?} depth profile</title>\n\
+             <line x1=\"0\" y1=\"{surface:.2}\" x2=\"{w}\" y2=\"{surface:.2}\" stroke=\"lightblue\"/>\n\
+             <polyline fill=\"none\" stroke=\"navy\" points=\"{}\"/>\n\
+             </svg>\n",
+            self.motion,
+            points,
+            w = SVG_WIDTH,
+            h = SVG_HEIGHT,
+            surface = -min_depth as f64 * y_scale,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn test_commands() -> Vec<Command> {
+        parser::parse("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2")
+            .expect("Unable to parse commands")
+    }
+
+    #[test]
+    fn test_record_coarse() {
+        let t = record(&test_commands(), Motion::Coarse);
+        assert_eq!(t.waypoints.len(), 7);
+        assert_eq!(
+            t.waypoints[0],
+            Waypoint {
+                step: 0,
+                distance: 0,
+                depth: 0,
+                aim: 0
+            }
+        );
+        assert_eq!(t.last().distance, 15);
+        assert_eq!(t.last().depth, 10);
+    }
+
+    #[test]
+    fn test_record_accurate() {
+        let t = record(&test_commands(), Motion::Accurate);
+        assert_eq!(
+            t.waypoints[3],
+            Waypoint {
+                step: 3,
+                distance: 13,
+                depth: 40,
+                aim: 5
+            }
+        );
+        assert_eq!(t.last().distance, 15);
+        assert_eq!(t.last().depth, 60);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let t = record(&test_commands()[..2], Motion::Accurate);
+        assert_eq!(
+            t.to_csv(),
+            "step,distance,depth,aim\n0,0,0,0\n1,5,0,0\n2,5,0,5\n"
+        );
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let t = record(&test_commands(), Motion::Coarse);
+        let svg = t.to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("<title>Coarse depth profile</title>"));
+        assert!(svg.contains("points=\"0.00,0.00 266.67,0.00 266.67,200.00"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -0,0 +1,80 @@
+// the benchmark only compares the rating engines, the rest of the modules is unused here
+#![allow(dead_code)]
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+#[cfg(test)]
+mod fixtures;
+// the tests of these modules are left out of the benchmark, but not the imports of their
+// test modules
+#[allow(unused_imports)]
+mod rating;
+#[allow(unused_imports)]
+mod report;
+#[allow(unused_imports)]
+mod sorted;
+#[allow(unused_imports)]
+mod trie;
+use rating::{BitOrder, RatingRule};
+use report::Report;
+use sorted::SortedReport;
+use trie::Trie;
+
+// deterministic pseudo-random report, so the two versions see the same readings
+fn generate_report(lines: usize, width: usize) -> Report<u32> {
+    let mut state: u64 = 0x2021_0003;
+    let readings = (0..lines)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((state >> 32) as u32) >> (32 - width)
+        })
+        .collect();
+
+    Report { width, readings }
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ratings");
+    group.sample_size(10);
+
+    for (lines, width) in [(10_000, 16), (1_000_000, 16), (1_000_000, 32)] {
+        let report = generate_report(lines, width);
+        let id = format!("{} lines, {} bits", lines, width);
+
+        group.bench_with_input(BenchmarkId::new("filtering", &id), &report, |b, r| {
+            b.iter(|| {
+                (
+                    RatingRule::oxygen().apply(r).value(),
+                    RatingRule::co2_scrubber().apply(r).value(),
+                )
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("sorted", &id), &report, |b, r| {
+            b.iter(|| {
+                let sorted = SortedReport::new(r, BitOrder::MsbFirst);
+                (
+                    sorted.rate(&RatingRule::oxygen()),
+                    sorted.rate(&RatingRule::co2_scrubber()),
+                )
+            })
+        });
+        if width <= trie::MAX_WIDTH {
+            group.bench_with_input(BenchmarkId::new("trie", &id), &report, |b, r| {
+                b.iter(|| {
+                    let trie = Trie::new(r, BitOrder::MsbFirst).unwrap();
+                    (
+                        trie.rate(&RatingRule::oxygen()),
+                        trie.rate(&RatingRule::co2_scrubber()),
+                    )
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
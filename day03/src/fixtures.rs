@@ -0,0 +1,19 @@
+use crate::report::{Reading, Report};
+
+// the example from the puzzle
+pub fn test_signals() -> Report<u16> {
+    Report::parse(
+        "00100\n11110\n10110\n10111\n10101\n01111\n00111\n11100\n10000\n11001\n00010\n01010",
+    )
+    .unwrap()
+}
+
+// oxygen ends on the two 101, CO2 on 011
+pub fn tied_report() -> Report<u16> {
+    Report::parse("101\n011\n101\n").unwrap()
+}
+
+pub fn puzzle_input<T: Reading>() -> Report<T> {
+    let content = std::fs::read_to_string("input").expect("Unable to read file");
+    Report::parse(&content).unwrap()
+}
@@ -1,92 +1,106 @@
-fn binary_to_decimal(bin: &str) -> isize {
-    isize::from_str_radix(bin, 2).unwrap()
-}
-
-fn filter_signals(signals: &Vec<String>, position: usize, bit: char) -> Vec<String> {
-    signals
-        .iter()
-        .filter(|s| s.chars().nth(position).unwrap() == bit)
-        .cloned()
-        .collect()
-}
-
-fn count_bits(signals: &Vec<String>, position: usize) -> (usize, usize) {
-    signals
+#[cfg(test)]
+mod fixtures;
+mod rating;
+mod report;
+mod sorted;
+mod trie;
+
+use num_bigint::BigUint;
+use rating::{BitOrder, RatingRule, Stop};
+use report::{Reading, Report, ReportError};
+use sorted::SortedReport;
+use trie::Trie;
+
+fn energy_rates<T: Reading>(report: &Report<T>) -> (u128, u128) {
+    let total = report.readings.len();
+    let gamma = report
+        .ones_per_position()
         .iter()
-        .map(|s| s.chars().nth(position).unwrap())
-        .fold((0, 0), |acc, c| match c {
-            '0' => (acc.0 + 1, acc.1),
-            '1' => (acc.0, acc.1 + 1),
-            _ => acc,
-        })
-}
-
-fn energy_rates(signals: &Vec<String>) -> (isize, isize) {
-    let signal_length = signals[0].len();
-    let gamma: String = (0..signal_length)
-        .map(|i| {
-            let (zeroes, ones) = count_bits(&signals, i);
-            if ones > zeroes {
-                "1"
-            } else {
-                "0"
-            }
-        })
-        .collect();
-
-    let gamma = binary_to_decimal(&gamma);
-    let epsilon = (1 << signal_length) - 1 - gamma;
+        .fold(0, |acc, &ones| (acc << 1) | (2 * ones > total) as u128);
+    let epsilon = report.mask() & !gamma;
 
     (gamma, epsilon)
 }
 
-fn oxygen_rating(signals: &Vec<String>, position: usize) -> isize {
-    if signals.len() == 1 {
-        return binary_to_decimal(&signals[0]);
+// one pass to index the readings, then each rating is a walk down the index
+fn indexed_ratings<T: Reading>(report: &Report<T>, rules: [RatingRule; 2]) -> [Option<u128>; 2] {
+    let order = rules[0].order;
+    match Trie::new(report, order) {
+        Some(trie) => rules.map(|rule| trie.rate(&rule).expect("Index built for another order")),
+        None => {
+            let sorted = SortedReport::new(report, order);
+            rules.map(|rule| sorted.rate(&rule).expect("Index built for another order"))
+        }
     }
-
-    let (zeroes, ones) = count_bits(signals, position);
-    let signals = filter_signals(&signals, position, if ones >= zeroes { '1' } else { '0' });
-    oxygen_rating(&signals, position + 1)
 }
 
-fn co2_scrubber_rating(signals: &Vec<String>, position: usize) -> isize {
-    if signals.len() == 1 {
-        return binary_to_decimal(&signals[0]);
-    }
+// the presets, with the bit order and the stop taken from the command line
+fn rules() -> [RatingRule; 2] {
+    let args: Vec<String> = std::env::args().collect();
+    let order = if args.iter().any(|arg| arg == "--lsb-first") {
+        BitOrder::LsbFirst
+    } else {
+        BitOrder::MsbFirst
+    };
+    let stop = if args.iter().any(|arg| arg == "--all-bits") {
+        Stop::AllBits
+    } else {
+        Stop::AtMost(1)
+    };
+
+    [RatingRule::oxygen(), RatingRule::co2_scrubber()].map(|preset| RatingRule {
+        order,
+        stop,
+        ..preset
+    })
+}
 
-    let (zeroes, ones) = count_bits(signals, position);
-    let signals = filter_signals(&signals, position, if ones >= zeroes { '0' } else { '1' });
-    co2_scrubber_rating(&signals, position + 1)
+// readings can be 128 bits wide, so the products can take 256
+fn product(a: u128, b: u128) -> BigUint {
+    BigUint::from(a) * b
 }
 
-fn main() {
-    let content = std::fs::read_to_string("input").expect("Unable to read file");
-    let signals: Vec<String> = content.lines().map(|line| line.into()).collect();
+fn run<T: Reading>(content: &str, rules: [RatingRule; 2]) -> Result<(), ReportError> {
+    let report: Report<T> = Report::parse(content)?;
 
-    let (gamma, epsilon) = energy_rates(&signals);
-    let oxygen_rating = oxygen_rating(&signals, 0);
-    let co2_scrubber_rating = co2_scrubber_rating(&signals, 0);
+    let (gamma, epsilon) = energy_rates(&report);
+    // the filtering engine is only needed to locate the lines when there's no single survivor
+    let [oxygen, co2_scrubber] = indexed_ratings(&report, rules);
+    let oxygen_rating = oxygen.map_or_else(|| rules[0].rate(&report), Ok)?;
+    let co2_scrubber_rating = co2_scrubber.map_or_else(|| rules[1].rate(&report), Ok)?;
 
-    println!("{} * {} = {}", gamma, epsilon, gamma * epsilon);
+    println!("{} * {} = {}", gamma, epsilon, product(gamma, epsilon));
     println!("Oxygen: {}", oxygen_rating);
     println!("CO2: {}", co2_scrubber_rating);
-    println!("Life Support: {}", oxygen_rating * co2_scrubber_rating);
+    println!(
+        "Life Support: {}",
+        product(oxygen_rating, co2_scrubber_rating)
+    );
+
+    Ok(())
+}
+
+fn main() {
+    let content = std::fs::read_to_string("input").expect("Unable to read file");
+    let rules = rules();
+
+    let result = match report::width_of(&content) {
+        0..=16 => run::<u16>(&content, rules),
+        17..=32 => run::<u32>(&content, rules),
+        33..=64 => run::<u64>(&content, rules),
+        _ => run::<u128>(&content, rules),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Invalid report: {}", e);
+        std::process::exit(1);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    fn test_signals() -> Vec<String> {
-        vec![
-            "00100", "11110", "10110", "10111", "10101", "01111", "00111", "11100", "10000",
-            "11001", "00010", "01010",
-        ]
-        .iter()
-        .map(|&s| s.into())
-        .collect()
-    }
+    use crate::fixtures::{puzzle_input, test_signals};
 
     #[test]
     fn test_energy_rates() {
@@ -98,14 +112,29 @@ mod tests {
     }
 
     #[test]
-    fn test_oxygen_rating() {
-        let signals = test_signals();
-        assert_eq!(oxygen_rating(&signals, 0), 23);
+    fn test_wide_products() {
+        let (a, b) = ("01".repeat(50), "10".repeat(50));
+        let content = format!("1{}\n1{}00\n0{}\n", a, &a[..98], b);
+        let report: Report<u128> = Report::parse(&content).unwrap();
+        assert_eq!(report.width, 101);
+
+        let (gamma, epsilon) = energy_rates(&report);
+        assert_eq!(product(gamma, epsilon), BigUint::from(gamma) * epsilon);
+        assert!(product(gamma, epsilon) > BigUint::from(u128::MAX));
+        assert_eq!(product(u128::MAX, 2), BigUint::from(u128::MAX) << 1);
+
+        let rules = [RatingRule::oxygen(), RatingRule::co2_scrubber()];
+        assert_eq!(run::<u128>(&content, rules), Ok(()));
     }
 
     #[test]
-    fn test_co2_scrubber_rating() {
-        let signals = test_signals();
-        assert_eq!(co2_scrubber_rating(&signals, 0), 10);
+    fn test_reading_types_agree() {
+        let narrow: Report<u16> = puzzle_input();
+        let wide: Report<u128> = puzzle_input();
+
+        assert_eq!(energy_rates(&narrow), energy_rates(&wide));
+        for rule in [RatingRule::oxygen(), RatingRule::co2_scrubber()] {
+            assert_eq!(rule.apply(&narrow).value(), rule.apply(&wide).value());
+        }
     }
 }
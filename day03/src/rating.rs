@@ -0,0 +1,207 @@
+use crate::report::{Reading, Report, ReportError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+    MostCommon,
+    LeastCommon,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+impl BitOrder {
+    // positions are counted from the most significant bit, like in the report
+    pub fn positions(self, width: usize) -> Box<dyn Iterator<Item = usize>> {
+        match self {
+            BitOrder::MsbFirst => Box::new(0..width),
+            BitOrder::LsbFirst => Box::new((0..width).rev()),
+        }
+    }
+}
+
+// the indexes are built for a bit order and can't rate with the other one
+#[derive(Debug, PartialEq)]
+pub struct OrderMismatch {
+    pub index: BitOrder,
+    pub rule: BitOrder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stop {
+    // stop as soon as there are at most this many candidates left
+    AtMost(usize),
+    // filter on every bit, only stopping early if no candidate is left
+    AllBits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RatingRule {
+    pub criterion: Criterion,
+    // bit to keep when zeroes and ones are equally common
+    pub tie_break: bool,
+    pub order: BitOrder,
+    pub stop: Stop,
+}
+
+// candidates left after filtering on a position, counted from the most significant bit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub position: usize,
+    pub candidates: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rating<T: Reading> {
+    pub survivors: Vec<T>,
+    pub trace: Vec<Step>,
+}
+
+impl<T: Reading> Rating<T> {
+    pub fn value(&self) -> Option<u128> {
+        match self.survivors[..] {
+            [survivor] => Some(survivor.value()),
+            _ => None,
+        }
+    }
+}
+
+impl RatingRule {
+    pub fn oxygen() -> RatingRule {
+        RatingRule {
+            criterion: Criterion::MostCommon,
+            tie_break: true,
+            order: BitOrder::MsbFirst,
+            stop: Stop::AtMost(1),
+        }
+    }
+
+    pub fn co2_scrubber() -> RatingRule {
+        RatingRule {
+            criterion: Criterion::LeastCommon,
+            tie_break: false,
+            order: BitOrder::MsbFirst,
+            stop: Stop::AtMost(1),
+        }
+    }
+
+    pub fn keep(&self, zeroes: usize, ones: usize) -> bool {
+        match self.criterion {
+            _ if zeroes == ones => self.tie_break,
+            Criterion::MostCommon => ones > zeroes,
+            Criterion::LeastCommon => ones < zeroes,
+        }
+    }
+
+    pub fn done(&self, candidates: usize) -> bool {
+        match self.stop {
+            Stop::AtMost(n) => candidates <= n,
+            Stop::AllBits => candidates == 0,
+        }
+    }
+
+    pub fn apply<T: Reading>(&self, report: &Report<T>) -> Rating<T> {
+        let mut survivors = report.readings.clone();
+        let mut trace = vec![];
+
+        for position in self.order.positions(report.width) {
+            if self.done(survivors.len()) {
+                break;
+            }
+
+            let ones = survivors
+                .iter()
+                .filter(|&&s| report.bit_at(s, position))
+                .count();
+            let bit = self.keep(survivors.len() - ones, ones);
+            survivors.retain(|&s| report.bit_at(s, position) == bit);
+            trace.push(Step {
+                position,
+                candidates: survivors.len(),
+            });
+        }
+
+        Rating { survivors, trace }
+    }
+
+    pub fn rate<T: Reading>(&self, report: &Report<T>) -> Result<u128, ReportError> {
+        let rating = self.apply(report);
+        rating.value().ok_or_else(|| ReportError::NoSingleSurvivor {
+            lines: report.lines_of(&rating.survivors),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{test_signals, tied_report};
+
+    fn candidates(rating: &Rating<u16>) -> Vec<usize> {
+        rating.trace.iter().map(|s| s.candidates).collect()
+    }
+
+    #[test]
+    fn test_oxygen_preset() {
+        let rating = RatingRule::oxygen().apply(&test_signals());
+        assert_eq!(rating.value(), Some(23));
+        assert_eq!(candidates(&rating), vec![7, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_co2_scrubber_preset() {
+        let rating = RatingRule::co2_scrubber().apply(&test_signals());
+        assert_eq!(rating.value(), Some(10));
+        assert_eq!(candidates(&rating), vec![5, 2, 1]);
+    }
+
+    #[test]
+    fn test_lsb_first() {
+        let rule = RatingRule {
+            order: BitOrder::LsbFirst,
+            ..RatingRule::oxygen()
+        };
+        let rating = rule.apply(&test_signals());
+        assert_eq!(rating.trace[0].position, 4);
+        assert_eq!(rating.value(), Some(0b11110));
+    }
+
+    #[test]
+    fn test_stop_with_several_survivors() {
+        let rule = RatingRule {
+            stop: Stop::AtMost(3),
+            ..RatingRule::oxygen()
+        };
+        let rating = rule.apply(&test_signals());
+        assert_eq!(rating.survivors, vec![0b10110, 0b10111, 0b10101]);
+        assert_eq!(rating.value(), None);
+    }
+
+    #[test]
+    fn test_all_bits_keeps_duplicates() {
+        let report: Report<u16> = Report::parse("101\n101\n001").unwrap();
+        let rule = RatingRule {
+            stop: Stop::AllBits,
+            ..RatingRule::oxygen()
+        };
+        let rating = rule.apply(&report);
+        assert_eq!(rating.survivors, vec![0b101, 0b101]);
+        assert_eq!(candidates(&rating), vec![2, 2, 2]);
+    }
+
+    #[test]
+    fn test_rate() {
+        assert_eq!(RatingRule::oxygen().rate(&test_signals()), Ok(23));
+    }
+
+    #[test]
+    fn test_rate_without_single_survivor() {
+        let report: Report<u16> = tied_report();
+        assert_eq!(
+            RatingRule::oxygen().rate(&report),
+            Err(ReportError::NoSingleSurvivor { lines: vec![1, 3] })
+        );
+    }
+}
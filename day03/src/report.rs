@@ -0,0 +1,302 @@
+use std::fmt::{Debug, Display, Error, Formatter};
+
+pub trait Reading: Copy + Ord + Debug {
+    const BITS: usize;
+
+    fn parse(bin: &str) -> Self;
+    fn value(self) -> u128;
+
+    // positions are counted from the least significant bit
+    fn bit(self, position: usize) -> bool {
+        (self.value() >> position) & 1 == 1
+    }
+}
+
+macro_rules! impl_reading {
+    ($($t:ty),*) => {
+        $(
+            impl Reading for $t {
+                const BITS: usize = <$t>::BITS as usize;
+
+                fn parse(bin: &str) -> Self {
+                    <$t>::from_str_radix(bin, 2).unwrap()
+                }
+
+                fn value(self) -> u128 {
+                    self as u128
+                }
+
+                fn bit(self, position: usize) -> bool {
+                    (self >> position) & 1 == 1
+                }
+            }
+        )*
+    };
+}
+
+impl_reading!(u16, u32, u64, u128);
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Report<T: Reading> {
+    pub width: usize,
+    pub readings: Vec<T>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReportError {
+    Empty,
+    EmptyLine {
+        line: usize,
+    },
+    RaggedWidth {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    TooWide {
+        width: usize,
+        bits: usize,
+    },
+    // lines of the candidates left when a rating stops, none if all were filtered out
+    NoSingleSurvivor {
+        lines: Vec<usize>,
+    },
+}
+
+impl Display for ReportError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            ReportError::Empty => write!(f, "the report is empty"),
+            ReportError::EmptyLine { line } => write!(f, "line {} is empty", line),
+            ReportError::RaggedWidth {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {} has {} bits, expected {}", line, found, expected),
+            ReportError::InvalidCharacter {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {}, column {}: '{}' is not a binary digit",
+                line, column, found
+            ),
+            ReportError::TooWide { width, bits } => {
+                write!(f, "{}-bit readings don't fit in {} bits", width, bits)
+            }
+            ReportError::NoSingleSurvivor { lines } if lines.is_empty() => {
+                write!(f, "no reading satisfies the rating")
+            }
+            ReportError::NoSingleSurvivor { lines } => write!(
+                f,
+                "no single survivor, candidates left on lines {:?}",
+                lines
+            ),
+        }
+    }
+}
+
+fn validate_line(line: &str, number: usize, width: usize) -> Result<(), ReportError> {
+    if let Some((idx, found)) = line
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !matches!(c, '0' | '1'))
+    {
+        return Err(ReportError::InvalidCharacter {
+            line: number,
+            column: idx + 1,
+            found,
+        });
+    }
+
+    match line.len() {
+        0 => Err(ReportError::EmptyLine { line: number }),
+        found if found != width => Err(ReportError::RaggedWidth {
+            line: number,
+            expected: width,
+            found,
+        }),
+        _ => Ok(()),
+    }
+}
+
+impl<T: Reading> Report<T> {
+    pub fn parse(content: &str) -> Result<Report<T>, ReportError> {
+        let width = content.lines().next().ok_or(ReportError::Empty)?.len();
+        if width > T::BITS {
+            return Err(ReportError::TooWide {
+                width,
+                bits: T::BITS,
+            });
+        }
+
+        let readings = content
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                validate_line(line, idx + 1, width)?;
+                // only '0' and '1' are left, and the width fits the type
+                Ok(T::parse(line))
+            })
+            .collect::<Result<Vec<T>, ReportError>>()?;
+
+        Ok(Report { width, readings })
+    }
+
+    // 1-based line numbers of the readings equal to any of the given values
+    pub fn lines_of(&self, values: &[T]) -> Vec<usize> {
+        self.readings
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| values.contains(r))
+            .map(|(idx, _)| idx + 1)
+            .collect()
+    }
+
+    // positions are counted from the most significant bit, like reading the line left to right
+    pub fn bit_at(&self, reading: T, position: usize) -> bool {
+        reading.bit(self.width - 1 - position)
+    }
+
+    pub fn ones_per_position(&self) -> Vec<usize> {
+        let mut ones = vec![0; self.width];
+        self.readings.iter().for_each(|&r| {
+            ones.iter_mut()
+                .enumerate()
+                .for_each(|(position, count)| *count += self.bit_at(r, position) as usize)
+        });
+        ones
+    }
+
+    pub fn mask(&self) -> u128 {
+        if self.width == 128 {
+            u128::MAX
+        } else {
+            (1 << self.width) - 1
+        }
+    }
+}
+
+// width of the first line, used to pick a reading type before parsing
+pub fn width_of(content: &str) -> usize {
+    content.lines().next().map_or(0, |line| line.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let report: Report<u16> = Report::parse("00100\n11110\n10110\n").unwrap();
+        assert_eq!(report.width, 5);
+        assert_eq!(report.readings, vec![4, 30, 22]);
+    }
+
+    #[test]
+    fn test_bit_at() {
+        let report: Report<u32> = Report::parse("00100\n").unwrap();
+        assert!(!report.bit_at(4, 0));
+        assert!(report.bit_at(4, 2));
+        assert!(!report.bit_at(4, 4));
+    }
+
+    #[test]
+    fn test_ones_per_position() {
+        let report: Report<u64> = Report::parse("00100\n11110\n10110\n").unwrap();
+        assert_eq!(report.ones_per_position(), vec![2, 1, 3, 2, 0]);
+    }
+
+    #[test]
+    fn test_wide_readings() {
+        let line = "1".repeat(128);
+        let report: Report<u128> = Report::parse(&line).unwrap();
+        assert_eq!(report.readings, vec![u128::MAX]);
+        assert_eq!(report.mask(), u128::MAX);
+    }
+
+    #[test]
+    fn test_too_wide() {
+        let line = "1".repeat(17);
+        let result: Result<Report<u16>, _> = Report::parse(&line);
+        assert_eq!(
+            result,
+            Err(ReportError::TooWide {
+                width: 17,
+                bits: 16
+            })
+        );
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let result: Result<Report<u16>, _> = Report::parse("");
+        assert_eq!(result, Err(ReportError::Empty));
+    }
+
+    #[test]
+    fn test_empty_line() {
+        let result: Result<Report<u16>, _> = Report::parse("101\n\n110\n");
+        assert_eq!(result, Err(ReportError::EmptyLine { line: 2 }));
+    }
+
+    #[test]
+    fn test_ragged_width() {
+        let result: Result<Report<u16>, _> = Report::parse("101\n110\n1100\n");
+        assert_eq!(
+            result,
+            Err(ReportError::RaggedWidth {
+                line: 3,
+                expected: 3,
+                found: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_character() {
+        let result: Result<Report<u16>, _> = Report::parse("101\n1x0\n");
+        assert_eq!(
+            result,
+            Err(ReportError::InvalidCharacter {
+                line: 2,
+                column: 2,
+                found: 'x'
+            })
+        );
+
+        let result: Result<Report<u16>, _> = Report::parse("+11\n");
+        assert!(matches!(
+            result,
+            Err(ReportError::InvalidCharacter { found: '+', .. })
+        ));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let report: Report<u16> = Report::parse("101\r\n110\r\n").unwrap();
+        assert_eq!(report.readings, vec![5, 6]);
+    }
+
+    #[test]
+    fn test_lines_of() {
+        let report: Report<u16> = Report::parse("101\n110\n101\n").unwrap();
+        assert_eq!(report.lines_of(&[0b101]), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = ReportError::RaggedWidth {
+            line: 3,
+            expected: 5,
+            found: 4,
+        };
+        assert_eq!(format!("{}", error), "line 3 has 4 bits, expected 5");
+    }
+}
@@ -0,0 +1,113 @@
+use crate::rating::{BitOrder, OrderMismatch, RatingRule};
+use crate::report::{Reading, Report};
+
+pub struct SortedReport<'a, T: Reading> {
+    report: &'a Report<T>,
+    order: BitOrder,
+    sorted: Vec<T>,
+}
+
+impl<'a, T: Reading> SortedReport<'a, T> {
+    // lsb first the readings are sorted as if their bits were reversed
+    pub fn new(report: &'a Report<T>, order: BitOrder) -> SortedReport<'a, T> {
+        let mut sorted = report.readings.clone();
+        match order {
+            BitOrder::MsbFirst => sorted.sort_unstable(),
+            BitOrder::LsbFirst => sorted.sort_by_cached_key(|&r| {
+                order
+                    .positions(report.width)
+                    .fold(0u128, |acc, p| (acc << 1) | report.bit_at(r, p) as u128)
+            }),
+        }
+        SortedReport {
+            report,
+            order,
+            sorted,
+        }
+    }
+
+    // readings sharing the bits before `position` are contiguous once sorted,
+    // and within them the ones with a zero at `position` come first;
+    // None when the rule doesn't end on a single survivor
+    pub fn rate(&self, rule: &RatingRule) -> Result<Option<u128>, OrderMismatch> {
+        if rule.order != self.order {
+            return Err(OrderMismatch {
+                index: self.order,
+                rule: rule.order,
+            });
+        }
+
+        let (mut lo, mut hi) = (0, self.sorted.len());
+
+        for position in self.order.positions(self.report.width) {
+            if rule.done(hi - lo) {
+                break;
+            }
+
+            let split =
+                lo + self.sorted[lo..hi].partition_point(|&r| !self.report.bit_at(r, position));
+            if rule.keep(split - lo, hi - split) {
+                lo = split;
+            } else {
+                hi = split;
+            }
+        }
+
+        Ok((hi - lo == 1).then(|| self.sorted[lo].value()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{puzzle_input, test_signals, tied_report};
+    use crate::rating::Stop;
+
+    #[test]
+    fn test_sorted_ratings() {
+        let report: Report<u16> = test_signals();
+        let sorted = SortedReport::new(&report, BitOrder::MsbFirst);
+        assert_eq!(sorted.rate(&RatingRule::oxygen()), Ok(Some(23)));
+        assert_eq!(sorted.rate(&RatingRule::co2_scrubber()), Ok(Some(10)));
+    }
+
+    #[test]
+    fn test_sorted_without_single_survivor() {
+        let report: Report<u16> = tied_report();
+        let sorted = SortedReport::new(&report, BitOrder::MsbFirst);
+        assert_eq!(sorted.rate(&RatingRule::oxygen()), Ok(None));
+        assert_eq!(sorted.rate(&RatingRule::co2_scrubber()), Ok(Some(0b011)));
+    }
+
+    #[test]
+    fn test_sorted_matches_engine() {
+        let report: Report<u64> = puzzle_input();
+        let sorted = SortedReport::new(&report, BitOrder::MsbFirst);
+
+        for rule in [RatingRule::oxygen(), RatingRule::co2_scrubber()] {
+            assert_eq!(sorted.rate(&rule), Ok(rule.apply(&report).value()));
+        }
+    }
+
+    #[test]
+    fn test_sorted_honors_order_and_stop() {
+        let report: Report<u64> = puzzle_input();
+
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let sorted = SortedReport::new(&report, order);
+            for stop in [Stop::AtMost(1), Stop::AtMost(3), Stop::AllBits] {
+                for preset in [RatingRule::oxygen(), RatingRule::co2_scrubber()] {
+                    let rule = RatingRule {
+                        order,
+                        stop,
+                        ..preset
+                    };
+                    assert_eq!(sorted.rate(&rule), Ok(rule.apply(&report).value()));
+                }
+            }
+        }
+        assert!(SortedReport::new(&report, BitOrder::LsbFirst)
+            .rate(&RatingRule::oxygen())
+            .is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::rating::{BitOrder, OrderMismatch, RatingRule};
+use crate::report::{Reading, Report};
+
+// 2^20 counters, beyond that the sorted report is used instead
+pub const MAX_WIDTH: usize = 20;
+
+// complete binary trie stored implicitly: the readings under a node whose prefix has
+// `length` bits are the values in [prefix << (width - length), (prefix + 1) << (width - length)),
+// so cumulative counts per value give the size of any node in O(1)
+pub struct Trie {
+    width: usize,
+    order: BitOrder,
+    cumulative: Vec<usize>,
+}
+
+impl Trie {
+    pub fn new<T: Reading>(report: &Report<T>, order: BitOrder) -> Option<Trie> {
+        if report.width > MAX_WIDTH {
+            return None;
+        }
+
+        let mut cumulative = vec![0; (1 << report.width) + 1];
+        report
+            .readings
+            .iter()
+            .for_each(|r| cumulative[key(r.value() as usize, report.width, order) + 1] += 1);
+        for i in 1..cumulative.len() {
+            cumulative[i] += cumulative[i - 1];
+        }
+
+        Some(Trie {
+            width: report.width,
+            order,
+            cumulative,
+        })
+    }
+
+    fn bounds(&self, prefix: usize, length: usize) -> (usize, usize) {
+        let shift = self.width - length;
+        (prefix << shift, (prefix + 1) << shift)
+    }
+
+    fn count(&self, prefix: usize, length: usize) -> usize {
+        let (lo, hi) = self.bounds(prefix, length);
+        self.cumulative[hi] - self.cumulative[lo]
+    }
+
+    // None when the rule doesn't end on a single survivor
+    pub fn rate(&self, rule: &RatingRule) -> Result<Option<u128>, OrderMismatch> {
+        if rule.order != self.order {
+            return Err(OrderMismatch {
+                index: self.order,
+                rule: rule.order,
+            });
+        }
+
+        let mut prefix = 0;
+        let mut length = 0;
+
+        while length < self.width && !rule.done(self.count(prefix, length)) {
+            let zeroes = self.count(prefix << 1, length + 1);
+            let ones = self.count((prefix << 1) | 1, length + 1);
+            prefix = (prefix << 1) | rule.keep(zeroes, ones) as usize;
+            length += 1;
+        }
+
+        if self.count(prefix, length) != 1 {
+            return Ok(None);
+        }
+
+        // the survivor is the only value under the node whose count goes up
+        let (lo, hi) = self.bounds(prefix, length);
+        let before = self.cumulative[lo];
+        let offset = self.cumulative[lo..=hi].partition_point(|&c| c == before);
+        Ok(Some(key(lo + offset - 1, self.width, self.order) as u128))
+    }
+}
+
+// lsb first the trie indexes the readings with their bits reversed,
+// reversing is its own inverse so the same function gets the reading back
+fn key(value: usize, width: usize, order: BitOrder) -> usize {
+    match order {
+        BitOrder::MsbFirst => value,
+        BitOrder::LsbFirst => value.reverse_bits() >> (usize::BITS as usize - width),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{puzzle_input, test_signals, tied_report};
+    use crate::rating::Stop;
+
+    #[test]
+    fn test_trie_counts() {
+        let trie = Trie::new(&test_signals(), BitOrder::MsbFirst).unwrap();
+        assert_eq!(trie.count(0, 0), 12);
+        assert_eq!(trie.count(1, 1), 7);
+        assert_eq!(trie.count(0, 1), 5);
+        assert_eq!(trie.count(0b10110, 5), 1);
+    }
+
+    #[test]
+    fn test_trie_ratings() {
+        let trie = Trie::new(&test_signals(), BitOrder::MsbFirst).unwrap();
+        assert_eq!(trie.rate(&RatingRule::oxygen()), Ok(Some(23)));
+        assert_eq!(trie.rate(&RatingRule::co2_scrubber()), Ok(Some(10)));
+    }
+
+    #[test]
+    fn test_trie_without_single_survivor() {
+        let report: Report<u16> = tied_report();
+        let trie = Trie::new(&report, BitOrder::MsbFirst).unwrap();
+        assert_eq!(trie.rate(&RatingRule::oxygen()), Ok(None));
+        assert_eq!(trie.rate(&RatingRule::co2_scrubber()), Ok(Some(0b011)));
+    }
+
+    #[test]
+    fn test_trie_too_wide() {
+        let report: Report<u32> = Report::parse(&"1".repeat(MAX_WIDTH + 1)).unwrap();
+        assert!(Trie::new(&report, BitOrder::MsbFirst).is_none());
+    }
+
+    #[test]
+    fn test_trie_matches_engine() {
+        let report: Report<u16> = puzzle_input();
+        let trie = Trie::new(&report, BitOrder::MsbFirst).unwrap();
+
+        for rule in [RatingRule::oxygen(), RatingRule::co2_scrubber()] {
+            assert_eq!(trie.rate(&rule), Ok(rule.apply(&report).value()));
+        }
+    }
+
+    #[test]
+    fn test_trie_honors_order_and_stop() {
+        let report: Report<u16> = puzzle_input();
+
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let trie = Trie::new(&report, order).unwrap();
+            for stop in [Stop::AtMost(1), Stop::AtMost(3), Stop::AllBits] {
+                for preset in [RatingRule::oxygen(), RatingRule::co2_scrubber()] {
+                    let rule = RatingRule {
+                        order,
+                        stop,
+                        ..preset
+                    };
+                    assert_eq!(trie.rate(&rule), Ok(rule.apply(&report).value()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_trie_lsb_first() {
+        let trie = Trie::new(&test_signals(), BitOrder::LsbFirst).unwrap();
+        let rule = RatingRule {
+            order: BitOrder::LsbFirst,
+            ..RatingRule::oxygen()
+        };
+        assert_eq!(trie.rate(&rule), Ok(Some(0b11110)));
+        assert_eq!(
+            trie.rate(&RatingRule::oxygen()),
+            Err(OrderMismatch {
+                index: BitOrder::LsbFirst,
+                rule: BitOrder::MsbFirst
+            })
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Cell {
     pub value: u64,
     pub extracted: bool,
@@ -18,75 +22,360 @@ impl From<u64> for Cell {
 impl Display for Cell {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         let output = format!("{}{}", self.value, if self.extracted { "*" } else { "" });
-        write!(f, "{:<4}", &output)
+        let width = f.width().unwrap_or(4);
+        write!(f, "{:<width$}", &output, width = width)
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum WinRule {
+    Row,
+    Column,
+    Diagonal,
+    FourCorners,
+    FullCard,
+}
+
+impl FromStr for WinRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<WinRule, String> {
+        match s {
+            "row" | "rows" => Ok(WinRule::Row),
+            "column" | "columns" => Ok(WinRule::Column),
+            "diagonal" | "diagonals" => Ok(WinRule::Diagonal),
+            "corners" => Ok(WinRule::FourCorners),
+            "full" => Ok(WinRule::FullCard),
+            _ => Err(format!("Unknown win rule '{}'", s)),
+        }
+    }
+}
+
+// what a draw marks when its value appears more than once on a board
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum DuplicatePolicy {
+    All,
+    First,
+    // one more occurrence every time the value is drawn
+    Next,
+}
+
+impl FromStr for DuplicatePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DuplicatePolicy, String> {
+        match s {
+            "all" => Ok(DuplicatePolicy::All),
+            "first" => Ok(DuplicatePolicy::First),
+            "next" => Ok(DuplicatePolicy::Next),
+            _ => Err(format!("Unknown duplicate policy '{}'", s)),
+        }
+    }
+}
+
+// what a draw of a repeated value marks, e.g. for warnings
+impl Display for DuplicatePolicy {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            DuplicatePolicy::All => write!(f, "every occurrence"),
+            DuplicatePolicy::First => write!(f, "only the first occurrence"),
+            DuplicatePolicy::Next => write!(f, "the next unmarked occurrence"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(into = "BoardState", try_from = "BoardState")]
 pub struct Board {
     cells: Vec<Cell>,
-    pub already_won: bool,
+    size: usize,
+    rules: Vec<WinRule>,
+    duplicates: DuplicatePolicy,
+    // cell positions for every value on the board
+    index: HashMap<u64, Vec<usize>>,
+    // cell positions of every line that wins under the current rules
+    lines: Vec<(WinRule, Vec<usize>)>,
+    // lines each cell is part of
+    cell_lines: Vec<Vec<usize>>,
+    // extracted cells on each line
+    marks: Vec<usize>,
+    // cell positions in the order they were marked, which decides the first line completed
+    marked: Vec<usize>,
+    // first line completed, if any
+    winning_line: Option<usize>,
+    unmarked_sum: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BoardError {
+    // the number of cells is not a square (or is zero)
+    NotSquare(usize),
+    // a saved mark points outside the board, at an unmarked cell or twice at the same cell
+    InvalidMark(usize),
+}
+
+impl Display for BoardError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            BoardError::NotSquare(n) => write!(f, "{} cells don't make a square board", n),
+            BoardError::InvalidMark(idx) => write!(f, "cell {} can't be marked", idx),
+        }
+    }
 }
 
-impl From<Vec<u64>> for Board {
-    fn from(v: Vec<u64>) -> Board {
-        Board {
+impl TryFrom<Vec<u64>> for Board {
+    type Error = BoardError;
+
+    fn try_from(v: Vec<u64>) -> Result<Board, BoardError> {
+        let size = (1..).find(|s| s * s >= v.len()).unwrap();
+        if v.is_empty() || size * size != v.len() {
+            return Err(BoardError::NotSquare(v.len()));
+        }
+
+        let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+        v.iter()
+            .enumerate()
+            .for_each(|(idx, &value)| index.entry(value).or_default().push(idx));
+
+        let mut board = Board {
             cells: v.iter().map(|&c| c.into()).collect(),
-            already_won: false,
+            size,
+            rules: vec![],
+            duplicates: DuplicatePolicy::All,
+            index,
+            lines: vec![],
+            cell_lines: vec![],
+            marks: vec![],
+            marked: vec![],
+            winning_line: None,
+            unmarked_sum: v.iter().sum(),
+        };
+        board.set_rules(&[WinRule::Row, WinRule::Column]);
+        Ok(board)
+    }
+}
+
+// what gets serialized: the indexes and counters are rebuilt when loading,
+// replaying the marks in their original order
+#[derive(Serialize, Deserialize)]
+struct BoardState {
+    cells: Vec<Cell>,
+    rules: Vec<WinRule>,
+    duplicates: DuplicatePolicy,
+    marked: Vec<usize>,
+}
+
+impl From<Board> for BoardState {
+    fn from(board: Board) -> BoardState {
+        BoardState {
+            cells: board.cells,
+            rules: board.rules,
+            duplicates: board.duplicates,
+            marked: board.marked,
+        }
+    }
+}
+
+impl TryFrom<BoardState> for Board {
+    type Error = BoardError;
+
+    fn try_from(state: BoardState) -> Result<Board, BoardError> {
+        let mut board = Board::try_from(state.cells.iter().map(|c| c.value).collect::<Vec<u64>>())?
+            .with_rules(&state.rules)
+            .with_duplicates(state.duplicates);
+        for &idx in &state.marked {
+            if !state.cells.get(idx).is_some_and(|c| c.extracted) || !board.mark(idx) {
+                return Err(BoardError::InvalidMark(idx));
+            }
+        }
+        // every extracted cell has its place in the order
+        if let Some(idx) = state
+            .cells
+            .iter()
+            .zip(&board.cells)
+            .position(|(saved, loaded)| saved.extracted && !loaded.extracted)
+        {
+            return Err(BoardError::InvalidMark(idx));
         }
+        Ok(board)
     }
 }
 
 impl Board {
-    fn rows(&self) -> Vec<Vec<&Cell>> {
-        (0..5)
-            .map(|row| (0..5).map(|col| self.at(row, col)).collect())
+    pub fn with_rules(mut self, rules: &[WinRule]) -> Board {
+        self.set_rules(rules);
+        self
+    }
+
+    pub fn with_duplicates(mut self, policy: DuplicatePolicy) -> Board {
+        self.duplicates = policy;
+        self
+    }
+
+    // values appearing more than once, in ascending order
+    pub fn duplicates(&self) -> Vec<u64> {
+        let mut values: Vec<u64> = self
+            .index
+            .iter()
+            .filter(|(_, positions)| positions.len() > 1)
+            .map(|(&value, _)| value)
+            .collect();
+        values.sort_unstable();
+        values
+    }
+
+    // rebuilds the line counters, keeping the cells already extracted
+    fn set_rules(&mut self, rules: &[WinRule]) {
+        self.rules = rules.to_vec();
+        self.lines = rules
+            .iter()
+            .flat_map(|&rule| {
+                self.rule_lines(rule)
+                    .into_iter()
+                    .map(move |cells| (rule, cells))
+            })
+            .collect();
+
+        self.cell_lines = vec![vec![]; self.cells.len()];
+        self.lines
+            .iter()
+            .enumerate()
+            .for_each(|(line, (_, cells))| {
+                cells
+                    .iter()
+                    .for_each(|&idx| self.cell_lines[idx].push(line))
+            });
+
+        self.marks = vec![0; self.lines.len()];
+        self.winning_line = None;
+        for i in 0..self.marked.len() {
+            self.count_mark(self.marked[i]);
+        }
+    }
+
+    // cell indices of every line that completes the board under the given rule
+    fn rule_lines(&self, rule: WinRule) -> Vec<Vec<usize>> {
+        let n = self.size;
+        match rule {
+            WinRule::Row => (0..n)
+                .map(|row| (0..n).map(|col| row * n + col).collect())
+                .collect(),
+            WinRule::Column => (0..n)
+                .map(|col| (0..n).map(|row| row * n + col).collect())
+                .collect(),
+            WinRule::Diagonal => vec![
+                (0..n).map(|i| i * n + i).collect(),
+                (0..n).map(|i| i * n + (n - 1 - i)).collect(),
+            ],
+            WinRule::FourCorners => {
+                let mut corners = vec![0, n - 1, n * (n - 1), n * n - 1];
+                // a 1x1 board has a single corner
+                corners.dedup();
+                vec![corners]
+            }
+            WinRule::FullCard => vec![(0..n * n).collect()],
+        }
+    }
+
+    fn cells_of(&self, rule: WinRule) -> Vec<Vec<&Cell>> {
+        self.rule_lines(rule)
+            .iter()
+            .map(|line| line.iter().map(|&idx| &self.cells[idx]).collect())
             .collect()
     }
 
+    fn rows(&self) -> Vec<Vec<&Cell>> {
+        self.cells_of(WinRule::Row)
+    }
+
+    #[cfg(test)]
     fn cols(&self) -> Vec<Vec<&Cell>> {
-        (0..5)
-            .map(|col| (0..5).map(|row| self.at(row, col)).collect())
-            .collect()
+        self.cells_of(WinRule::Column)
     }
 
+    #[cfg(test)]
     pub fn at(&self, row: usize, col: usize) -> &Cell {
-        &self.cells[row * 5 + col]
+        &self.cells[row * self.size + col]
     }
 
+    // true if the draw marked a cell that wasn't marked yet
     pub fn extract(&mut self, value: u64) -> bool {
-        match self.cells.iter_mut().find(|c| c.value == value) {
-            None => false,
-            Some(cell) => {
-                cell.extracted = true;
-                true
+        let positions = match self.index.get(&value) {
+            None => return false,
+            Some(positions) => positions.clone(),
+        };
+
+        match self.duplicates {
+            DuplicatePolicy::All => positions
+                .iter()
+                .fold(false, |marked, &idx| self.mark(idx) | marked),
+            DuplicatePolicy::First => self.mark(positions[0]),
+            DuplicatePolicy::Next => positions
+                .iter()
+                .find(|&&idx| !self.cells[idx].extracted)
+                .is_some_and(|&idx| self.mark(idx)),
+        }
+    }
+
+    fn mark(&mut self, idx: usize) -> bool {
+        let cell = &mut self.cells[idx];
+        if cell.extracted {
+            return false;
+        }
+
+        cell.extracted = true;
+        self.unmarked_sum -= cell.value;
+        self.marked.push(idx);
+        self.count_mark(idx);
+        true
+    }
+
+    fn count_mark(&mut self, idx: usize) {
+        for &line in &self.cell_lines[idx] {
+            self.marks[line] += 1;
+            if self.marks[line] == self.lines[line].1.len() && self.winning_line.is_none() {
+                self.winning_line = Some(line);
             }
         }
     }
 
     pub fn winning(&self) -> bool {
-        self.rows()
-            .iter()
-            .chain(self.cols().iter())
-            .any(|rowcol| rowcol.iter().all(|c| c.extracted))
+        self.winning_line.is_some()
+    }
+
+    // rule and values of the first line completed
+    pub fn winning_line(&self) -> Option<(WinRule, Vec<u64>)> {
+        self.winning_line.map(|line| {
+            let (rule, cells) = &self.lines[line];
+            (
+                *rule,
+                cells.iter().map(|&idx| self.cells[idx].value).collect(),
+            )
+        })
     }
 
     pub fn calculate_score(&self) -> u64 {
-        self.cells
-            .iter()
-            .filter(|c| !c.extracted)
-            .fold(0, |acc, c| acc + c.value)
+        self.unmarked_sum
     }
 }
 
 impl Display for Board {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        // room for the widest value, its mark and a space, but never narrower than 4
+        let width = self
+            .cells
+            .iter()
+            .map(|c| c.value.to_string().len() + 2)
+            .max()
+            .unwrap_or(0)
+            .max(4);
+
         let output = self
             .rows()
             .iter()
             .map(|row| {
                 row.iter()
-                    .map(|c| format!("{}", c))
+                    .map(|c| format!("{:width$}", c, width = width))
                     .collect::<Vec<String>>()
                     .join("")
             })
@@ -109,10 +398,15 @@ mod tests {
     }
 
     fn test_board() -> Board {
-        Board::from(vec![
+        Board::try_from(vec![
             14, 21, 17, 24, 4, 10, 16, 15, 9, 19, 18, 8, 23, 26, 20, 22, 11, 13, 6, 5, 2, 0, 12, 3,
             7,
         ])
+        .unwrap()
+    }
+
+    fn values(cells: &[&Cell]) -> Vec<u64> {
+        cells.iter().map(|c| c.value).collect()
     }
 
     #[test]
@@ -120,8 +414,7 @@ mod tests {
         let b = test_board();
 
         let rows = b.rows();
-        let expected: Board = vec![14, 21, 17, 24, 4].into();
-        assert_eq!(rows[0], expected.cells.iter().collect::<Vec<&Cell>>())
+        assert_eq!(values(&rows[0]), vec![14, 21, 17, 24, 4])
     }
 
     #[test]
@@ -129,8 +422,7 @@ mod tests {
         let b = test_board();
 
         let cols = b.cols();
-        let expected: Board = vec![14, 10, 18, 22, 2].into();
-        assert_eq!(cols[0], expected.cells.iter().collect::<Vec<&Cell>>())
+        assert_eq!(values(&cols[0]), vec![14, 10, 18, 22, 2])
     }
 
     #[test]
@@ -175,4 +467,222 @@ mod tests {
             &output
         )
     }
+
+    #[test]
+    fn test_not_square() {
+        assert_eq!(
+            Board::try_from(vec![1, 2, 3]),
+            Err(BoardError::NotSquare(3))
+        );
+        assert_eq!(Board::try_from(vec![]), Err(BoardError::NotSquare(0)));
+    }
+
+    #[test]
+    fn test_size_inferred() {
+        let b = Board::try_from((1..=9).collect::<Vec<u64>>()).unwrap();
+        assert_eq!(b.size, 3);
+        assert_eq!(b.at(2, 0).value, 7);
+        assert_eq!(values(&b.cols()[1]), vec![2, 5, 8]);
+    }
+
+    #[test]
+    fn test_diagonal_rule() {
+        let mut b = test_board();
+        extract!(&mut b, 14, 16, 23, 6, 7);
+        assert!(!b.winning());
+
+        let mut b = test_board().with_rules(&[WinRule::Diagonal]);
+        extract!(&mut b, 4, 9, 23, 11, 2);
+        assert!(b.winning());
+    }
+
+    #[test]
+    fn test_four_corners_rule() {
+        let mut b = test_board().with_rules(&[WinRule::FourCorners]);
+        extract!(&mut b, 14, 4, 2);
+        assert!(!b.winning());
+        extract!(&mut b, 7);
+        assert!(b.winning());
+    }
+
+    #[test]
+    fn test_full_card_rule() {
+        let mut b = Board::try_from(vec![1, 2, 3, 4])
+            .unwrap()
+            .with_rules(&[WinRule::FullCard]);
+        extract!(&mut b, 1, 2, 3);
+        assert!(!b.winning());
+        extract!(&mut b, 4);
+        assert!(b.winning());
+        assert_eq!(b.calculate_score(), 0);
+    }
+
+    #[test]
+    fn test_display_wide_values() {
+        let mut b = Board::try_from(vec![1000, 2, 3, 4]).unwrap();
+        extract!(&mut b, 1000);
+        assert_eq!(format!("{}", b), "1000* 2     \n3     4     ");
+    }
+
+    #[test]
+    fn test_extract_twice() {
+        let mut b = test_board();
+        assert!(b.extract(14));
+        assert!(!b.extract(14));
+        extract!(&mut b, 10, 18, 22);
+        assert!(!b.winning());
+        extract!(&mut b, 2);
+        assert!(b.winning());
+        assert_eq!(b.calculate_score(), 259);
+    }
+
+    #[test]
+    fn test_extract_missing() {
+        let mut b = test_board();
+        assert!(!b.extract(99));
+        assert_eq!(b.calculate_score(), 325);
+    }
+
+    #[test]
+    fn test_rules_changed_after_extraction() {
+        let mut b = test_board();
+        extract!(&mut b, 14, 16, 23, 6, 7);
+        assert!(!b.winning());
+        let b = b.with_rules(&[WinRule::Diagonal]);
+        assert!(b.winning());
+    }
+
+    #[test]
+    fn test_winning_line() {
+        let mut b = test_board().with_rules(&[WinRule::Row, WinRule::Diagonal]);
+        extract!(&mut b, 4, 9, 23, 11);
+        assert_eq!(b.winning_line(), None);
+        extract!(&mut b, 2);
+        assert_eq!(
+            b.winning_line(),
+            Some((WinRule::Diagonal, vec![4, 9, 23, 11, 2]))
+        );
+        extract!(&mut b, 0, 12, 3, 7);
+        assert_eq!(
+            b.winning_line(),
+            Some((WinRule::Diagonal, vec![4, 9, 23, 11, 2]))
+        );
+    }
+
+    fn duplicates_board() -> Board {
+        Board::try_from(vec![5, 1, 2, 5, 3, 4, 5, 6, 7]).unwrap()
+    }
+
+    #[test]
+    fn test_duplicates() {
+        assert_eq!(duplicates_board().duplicates(), vec![5]);
+        assert!(test_board().duplicates().is_empty());
+    }
+
+    #[test]
+    fn test_duplicates_mark_all() {
+        let mut b = duplicates_board();
+        assert!(b.extract(5));
+        assert!(b.winning());
+        assert_eq!(b.winning_line(), Some((WinRule::Column, vec![5, 5, 5])));
+        assert_eq!(b.calculate_score(), 1 + 2 + 3 + 4 + 6 + 7);
+    }
+
+    #[test]
+    fn test_duplicates_mark_first() {
+        let mut b = duplicates_board().with_duplicates(DuplicatePolicy::First);
+        assert!(b.extract(5));
+        assert!(!b.extract(5));
+        assert!(!b.winning());
+        assert_eq!(b.calculate_score(), 1 + 2 + 5 + 3 + 4 + 5 + 6 + 7);
+    }
+
+    #[test]
+    fn test_duplicates_mark_next() {
+        let mut b = duplicates_board().with_duplicates(DuplicatePolicy::Next);
+        extract!(&mut b, 5, 5);
+        assert!(!b.winning());
+        extract!(&mut b, 5);
+        assert!(b.winning());
+        // every 5 is marked already
+        assert!(!b.extract(5));
+        assert_eq!(b.calculate_score(), 1 + 2 + 3 + 4 + 6 + 7);
+    }
+
+    #[test]
+    fn test_single_cell_corners() {
+        let mut b = Board::try_from(vec![5])
+            .unwrap()
+            .with_rules(&[WinRule::FourCorners]);
+        extract!(&mut b, 5);
+        assert!(b.winning());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut b = test_board().with_rules(&[WinRule::Row, WinRule::Diagonal]);
+        extract!(&mut b, 4, 9, 23, 11, 2, 21);
+
+        let json = serde_json::to_string(&b).unwrap();
+        let loaded: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, b);
+        assert!(loaded.winning());
+        assert_eq!(loaded.calculate_score(), b.calculate_score());
+    }
+
+    #[test]
+    fn test_json_round_trip_keeps_winning_line() {
+        // the diagonal completes before the first row, which comes first in cell order
+        let mut b = test_board().with_rules(&[WinRule::Row, WinRule::Diagonal]);
+        extract!(&mut b, 14, 16, 23, 6, 7, 21, 17, 24, 4);
+        assert_eq!(
+            b.winning_line(),
+            Some((WinRule::Diagonal, vec![14, 16, 23, 6, 7]))
+        );
+
+        let json = serde_json::to_string(&b).unwrap();
+        let loaded: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.winning_line(), b.winning_line());
+        assert_eq!(loaded, b);
+    }
+
+    #[test]
+    fn test_json_invalid_mark() {
+        for marked in ["[1]", "[5]", "[0,0]", "[]"] {
+            let json = format!(
+                r#"{{"cells":[{{"value":1,"extracted":true}},{{"value":2,"extracted":false}},{{"value":3,"extracted":false}},{{"value":4,"extracted":false}}],"rules":[],"duplicates":"All","marked":{}}}"#,
+                marked
+            );
+            let result: Result<Board, _> = serde_json::from_str(&json);
+            assert!(result.is_err(), "{}", marked);
+        }
+    }
+
+    #[test]
+    fn test_json_format() {
+        let mut b = Board::try_from(vec![1]).unwrap();
+        b.extract(1);
+        assert_eq!(
+            serde_json::to_string(&b).unwrap(),
+            r#"{"cells":[{"value":1,"extracted":true}],"rules":["Row","Column"],"duplicates":"All","marked":[0]}"#
+        );
+    }
+
+    #[test]
+    fn test_json_not_square() {
+        let json = r#"{"cells":[{"value":1,"extracted":false},{"value":2,"extracted":false}],"rules":[],"duplicates":"All"}"#;
+        let result: Result<Board, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_win_rule() {
+        assert_eq!("diagonal".parse(), Ok(WinRule::Diagonal));
+        assert!("zigzag".parse::<WinRule>().is_err());
+        assert_eq!("next".parse(), Ok(DuplicatePolicy::Next));
+        assert_eq!(
+            DuplicatePolicy::First.to_string(),
+            "only the first occurrence"
+        );
+    }
 }
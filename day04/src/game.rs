@@ -0,0 +1,165 @@
+use crate::board::{Board, WinRule};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Win {
+    pub board: usize,
+    pub draw: u64,
+    // 1-based position of the draw in the sequence
+    pub turn: usize,
+    pub rule: WinRule,
+    pub line: Vec<u64>,
+    pub score: u64,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Rank {
+    pub position: usize,
+    pub board: usize,
+    // boards that never win are ranked last, in board order
+    pub win: Option<Win>,
+}
+
+pub struct Game {
+    draws: Vec<u64>,
+    boards: Vec<Board>,
+}
+
+impl Game {
+    pub fn new(draws: Vec<u64>, boards: Vec<Board>) -> Game {
+        Game { draws, boards }
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    // replays the draws once, and boards stop being marked once they won,
+    // so they keep the state they had when winning
+    pub fn play(&mut self) -> Vec<Win> {
+        let mut events = vec![];
+
+        for (turn, &draw) in self.draws.iter().enumerate() {
+            for (idx, board) in self.boards.iter_mut().enumerate() {
+                if board.winning() || !board.extract(draw) || !board.winning() {
+                    continue;
+                }
+
+                let (rule, line) = board.winning_line().unwrap();
+                events.push(Win {
+                    board: idx,
+                    draw,
+                    turn: turn + 1,
+                    rule,
+                    line,
+                    score: board.calculate_score() * draw,
+                });
+            }
+
+            if events.len() == self.boards.len() {
+                break;
+            }
+        }
+
+        events
+    }
+
+    pub fn ranking(&self, events: &[Win]) -> Vec<Rank> {
+        let mut won = vec![false; self.boards.len()];
+        events.iter().for_each(|win| won[win.board] = true);
+
+        let winners = events.iter().map(|win| (win.board, Some(win.clone())));
+        let losers = (0..self.boards.len())
+            .filter(|&idx| !won[idx])
+            .map(|idx| (idx, None));
+
+        winners
+            .chain(losers)
+            .enumerate()
+            .map(|(position, (board, win))| Rank {
+                position: position + 1,
+                board,
+                win,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn test_game() -> Game {
+        let draws = vec![
+            7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19,
+            3, 26, 1,
+        ];
+        let boards = vec![
+            vec![
+                22, 13, 17, 11, 0, 8, 2, 23, 4, 24, 21, 9, 14, 16, 7, 6, 10, 3, 18, 5, 1, 12, 20,
+                15, 19,
+            ],
+            vec![
+                3, 15, 0, 2, 22, 9, 18, 13, 17, 5, 19, 8, 7, 25, 23, 20, 11, 10, 24, 4, 14, 21, 16,
+                12, 6,
+            ],
+            vec![
+                14, 21, 17, 24, 4, 10, 16, 15, 9, 19, 18, 8, 23, 26, 20, 22, 11, 13, 6, 5, 2, 0,
+                12, 3, 7,
+            ],
+            vec![
+                90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107,
+                108, 109, 110, 111, 112, 113, 114,
+            ],
+        ];
+
+        Game::new(
+            draws,
+            boards
+                .into_iter()
+                .map(|b| Board::try_from(b).unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_play() {
+        let mut game = test_game();
+        let events = game.play();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            Win {
+                board: 2,
+                draw: 24,
+                turn: 12,
+                rule: WinRule::Row,
+                line: vec![14, 21, 17, 24, 4],
+                score: 4512,
+            }
+        );
+        assert_eq!(events[2].board, 1);
+        assert_eq!(events[2].draw, 13);
+        assert_eq!(events[2].score, 1924);
+    }
+
+    #[test]
+    fn test_ranking() {
+        let mut game = test_game();
+        let events = game.play();
+        let ranking = game.ranking(&events);
+
+        let order: Vec<usize> = ranking.iter().map(|r| r.board).collect();
+        assert_eq!(order, vec![2, 0, 1, 3]);
+        assert_eq!(ranking[3].position, 4);
+        assert_eq!(ranking[3].win, None);
+    }
+
+    #[test]
+    fn test_winners_stop_being_marked() {
+        let mut game = test_game();
+        game.play();
+        assert_eq!(game.boards()[2].calculate_score(), 188);
+    }
+}
@@ -0,0 +1,190 @@
+use rand::seq::{index, SliceRandom};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt::{Display, Error, Formatter};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Generator {
+    pub cards: usize,
+    pub size: usize,
+    pub values: RangeInclusive<u64>,
+    pub seed: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GeneratorError {
+    // a card needs size * size distinct values
+    RangeTooSmall { needed: usize, available: u64 },
+    ZeroSize,
+}
+
+impl Display for GeneratorError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            GeneratorError::RangeTooSmall { needed, available } => write!(
+                f,
+                "a card needs {} distinct values, the range only has {}",
+                needed, available
+            ),
+            GeneratorError::ZeroSize => write!(f, "cards need at least one row"),
+        }
+    }
+}
+
+impl Generator {
+    fn available(&self) -> u64 {
+        if self.values.is_empty() {
+            0
+        } else {
+            (self.values.end() - self.values.start()).saturating_add(1)
+        }
+    }
+
+    // the draws are every value on some card, shuffled, so a huge range only costs
+    // what the cards hold; the same seed always gives the same game
+    pub fn generate(&self) -> Result<(Vec<u64>, Vec<Vec<u64>>), GeneratorError> {
+        if self.size == 0 {
+            return Err(GeneratorError::ZeroSize);
+        }
+        let needed = self.size * self.size;
+        let available = self.available();
+        if available < needed as u64 {
+            return Err(GeneratorError::RangeTooSmall { needed, available });
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let start = *self.values.start();
+
+        let cards: Vec<Vec<u64>> = (0..self.cards)
+            .map(|_| {
+                index::sample(&mut rng, available as usize, needed)
+                    .iter()
+                    .map(|i| start + i as u64)
+                    .collect()
+            })
+            .collect();
+
+        let mut draws: Vec<u64> = cards.iter().flatten().copied().collect();
+        draws.sort_unstable();
+        draws.dedup();
+        draws.shuffle(&mut rng);
+
+        Ok((draws, cards))
+    }
+}
+
+// same layout as the puzzle input: draws, then the cards as right-aligned rows separated by blank lines
+pub fn write_input(draws: &[u64], cards: &[Vec<u64>]) -> String {
+    let width = cards
+        .iter()
+        .flatten()
+        .map(|v| v.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    let draws = draws
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+
+    let cards = cards
+        .iter()
+        .map(|card| {
+            let size = (1..).find(|s| s * s >= card.len()).unwrap();
+            card.chunks(size)
+                .map(|row| {
+                    row.iter()
+                        .map(|v| format!("{:>width$}", v, width = width))
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    format!("{}\n\n{}\n", draws, cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn generator() -> Generator {
+        Generator {
+            cards: 3,
+            size: 5,
+            values: 0..=99,
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let (draws, cards) = generator().generate().unwrap();
+        assert_eq!(cards.len(), 3);
+
+        let drawn: HashSet<u64> = draws.iter().copied().collect();
+        assert_eq!(drawn.len(), draws.len());
+        assert_eq!(drawn, cards.iter().flatten().copied().collect());
+
+        for card in cards {
+            assert_eq!(card.len(), 25);
+            assert_eq!(card.iter().collect::<HashSet<_>>().len(), 25);
+            assert!(card.iter().all(|v| (0..=99).contains(v)));
+        }
+    }
+
+    #[test]
+    fn test_generate_huge_range() {
+        let g = Generator {
+            values: 0..=u64::MAX,
+            ..generator()
+        };
+        let (draws, cards) = g.generate().unwrap();
+        assert_eq!(cards.len(), 3);
+        assert!(draws.len() <= 75);
+    }
+
+    #[test]
+    fn test_generate_is_seeded() {
+        assert_eq!(generator().generate(), generator().generate());
+
+        let other = Generator {
+            seed: 43,
+            ..generator()
+        };
+        assert_ne!(generator().generate(), other.generate());
+    }
+
+    #[test]
+    fn test_range_too_small() {
+        let g = Generator {
+            values: 10..=20,
+            ..generator()
+        };
+        assert_eq!(
+            g.generate(),
+            Err(GeneratorError::RangeTooSmall {
+                needed: 25,
+                available: 11
+            })
+        );
+
+        let g = Generator {
+            size: 0,
+            ..generator()
+        };
+        assert_eq!(g.generate(), Err(GeneratorError::ZeroSize));
+    }
+
+    #[test]
+    fn test_write_input() {
+        let output = write_input(&[7, 4, 10], &[vec![1, 2, 3, 10], vec![5, 6, 7, 8]]);
+        assert_eq!(output, "7,4,10\n\n 1  2\n 3 10\n\n 5  6\n 7  8\n");
+    }
+}
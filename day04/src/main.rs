@@ -1,92 +1,190 @@
 mod board;
-use board::Board;
+mod game;
+mod generator;
+mod parser;
+
+use board::{Board, DuplicatePolicy, WinRule};
+use game::Game;
+use generator::Generator;
+use parser::InputError;
+use std::convert::TryFrom;
 
 fn main() {
-    first_winner();
-    last_winner();
-}
+    let settings = settings();
+    if let Some(generator) = &settings.generate {
+        let (draws, cards) = generator
+            .generate()
+            .unwrap_or_else(|e| panic!("Unable to generate cards: {}", e));
+        print!("{}", generator::write_input(&draws, &cards));
+        return;
+    }
 
-fn first_winner() {
-    let (extractions, mut boards) = load_boards_and_inputs();
-    for e in extractions {
-        for board in &mut boards {
-            let found = board.extract(e);
-            if found && board.winning() {
-                println!(
-                    "First winner score: {}\n{}\n",
-                    board.calculate_score() * e,
-                    board
-                );
-                return;
-            }
-        }
+    let (extractions, boards) = load_boards_and_inputs(&settings);
+    let mut game = Game::new(extractions, boards);
+    let events = game.play();
+
+    if let Some(first) = events.first() {
+        println!(
+            "First winner score: {}\n{}\n",
+            first.score,
+            game.boards()[first.board]
+        );
     }
-}
 
-fn last_winner() {
-    let mut last_winner = 0;
-    let mut last_board: Option<usize> = None;
-
-    let (extractions, mut boards) = load_boards_and_inputs();
-    for e in extractions {
-        boards.iter_mut().enumerate().for_each(|(idx, board)| {
-            let found = board.extract(e);
-            if found && !board.already_won && board.winning() {
-                board.already_won = true;
-                last_winner = board.calculate_score() * e;
-                last_board = Some(idx);
-            }
-        });
+    if let Some(last) = events.last() {
+        println!(
+            "Last winner score: {}\n{}\n",
+            last.score,
+            game.boards()[last.board]
+        );
     }
 
-    let last_board = &boards[last_board.unwrap()];
-    println!("Last winner score: {}\n{}\n", last_winner, last_board);
+    for rank in game.ranking(&events) {
+        match rank.win {
+            Some(win) => println!(
+                "#{} board {}: won on turn {} drawing {} with {:?} {:?}, score {}",
+                rank.position, rank.board, win.turn, win.draw, win.rule, win.line, win.score
+            ),
+            None => println!("#{} board {}: never won", rank.position, rank.board),
+        }
+    }
+
+    if settings.json {
+        let json = serde_json::to_string_pretty(game.boards()).expect("Unable to serialize boards");
+        println!("{}", json);
+    }
 }
 
-fn load_boards_and_inputs() -> (Vec<u64>, Vec<Board>) {
-    let input_file = std::fs::read_to_string("input").expect("Unable to read input file");
-    let mut lines = input_file.lines();
-    let extractions: Vec<u64> = lines
-        .next()
-        .expect("No lines in file?")
-        .split(",")
-        .map(|x| x.parse::<u64>().unwrap())
-        .collect();
+struct Settings {
+    rules: Vec<WinRule>,
+    duplicates: DuplicatePolicy,
+    generate: Option<Generator>,
+    json: bool,
+}
 
-    lines.next();
-    let mut boards: Vec<Board> = vec![];
+// win rules can be given as arguments (e.g. `row column diagonal`), rows and columns by default;
+// `--duplicates=first` or `--duplicates=next` change how repeated values are marked,
+// `--json` dumps the final boards and `--generate=cards,size,min,max,seed` prints a new input
+fn settings() -> Settings {
+    let mut settings = Settings {
+        rules: vec![],
+        duplicates: DuplicatePolicy::All,
+        generate: None,
+        json: false,
+    };
 
-    let mut current_board: Vec<u64> = vec![];
-    while let Some(line) = lines.next() {
-        if line == "" {
-            boards.push(Board::from(current_board));
-            current_board = vec![];
+    for arg in std::env::args().skip(1) {
+        if let Some(policy) = arg.strip_prefix("--duplicates=") {
+            settings.duplicates = policy.parse().expect("Invalid duplicate policy");
+        } else if let Some(params) = arg.strip_prefix("--generate=") {
+            settings.generate = Some(parse_generator(params));
+        } else if arg == "--json" {
+            settings.json = true;
         } else {
-            line.split_whitespace().for_each(|x| {
-                let cell = x.parse::<u64>().unwrap();
-                current_board.push(cell);
-            });
+            settings.rules.push(arg.parse().expect("Invalid win rule"));
         }
     }
 
-    if current_board != vec![] {
-        boards.push(Board::from(current_board));
+    if settings.rules.is_empty() {
+        settings.rules = vec![WinRule::Row, WinRule::Column];
     }
+    settings
+}
+
+fn parse_generator(params: &str) -> Generator {
+    let params = params
+        .split(',')
+        .map(|p| p.parse::<u64>().expect("Invalid generator parameter"))
+        .collect::<Vec<u64>>();
+
+    match params[..] {
+        [cards, size, min, max, seed] => Generator {
+            cards: cards as usize,
+            size: size as usize,
+            values: min..=max,
+            seed,
+        },
+        _ => panic!("Expected --generate=cards,size,min,max,seed"),
+    }
+}
+
+fn load_boards_and_inputs(settings: &Settings) -> (Vec<u64>, Vec<Board>) {
+    let input_file = std::fs::read_to_string("input").expect("Unable to read input file");
+    parse_boards_and_inputs(&input_file, settings)
+        .unwrap_or_else(|e| panic!("Invalid input: {}", e))
+}
+
+// errors point at the line of the bad number or incomplete board
+fn parse_boards_and_inputs(
+    input_file: &str,
+    settings: &Settings,
+) -> Result<(Vec<u64>, Vec<Board>), InputError> {
+    let (extractions, cards) = parser::parse(input_file)?;
+    let boards: Vec<Board> = cards
+        .into_iter()
+        .map(|cells| load_board(cells, settings))
+        .collect();
+
+    boards.iter().enumerate().for_each(|(idx, board)| {
+        let duplicates = board.duplicates();
+        if !duplicates.is_empty() {
+            eprintln!(
+                "Warning: board {} repeats {:?}, a draw marks {}",
+                idx, duplicates, settings.duplicates
+            );
+        }
+    });
+
+    Ok((extractions, boards))
+}
 
-    (extractions, boards)
+fn load_board(cells: Vec<u64>, settings: &Settings) -> Board {
+    Board::try_from(cells)
+        .unwrap_or_else(|e| panic!("Invalid board: {}", e))
+        .with_rules(&settings.rules)
+        .with_duplicates(settings.duplicates)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn default_settings() -> Settings {
+        Settings {
+            rules: vec![WinRule::Row, WinRule::Column],
+            duplicates: DuplicatePolicy::All,
+            generate: None,
+            json: false,
+        }
+    }
+
     #[test]
     fn test_load_boards_and_inputs() {
-        let (extractions, boards) = load_boards_and_inputs();
+        let (extractions, boards) = load_boards_and_inputs(&default_settings());
         assert_eq!(extractions[0], 15);
 
         assert_eq!(boards[0].at(0, 0).value, 26);
         assert_eq!(boards.len(), 100);
         assert_eq!(boards[99].at(0, 0).value, 67);
     }
+
+    #[test]
+    fn test_generated_input_round_trip() {
+        let generator = parse_generator("10,7,1,500,2021");
+        let (draws, cards) = generator.generate().unwrap();
+        let input = generator::write_input(&draws, &cards);
+
+        let (extractions, boards) = parse_boards_and_inputs(&input, &default_settings()).unwrap();
+        assert_eq!(extractions, draws);
+        assert_eq!(boards.len(), 10);
+        for (board, card) in boards.iter().zip(cards.iter()) {
+            assert_eq!(*board, Board::try_from(card.clone()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_parse_error_is_located() {
+        let error = parse_boards_and_inputs("1,2\n\n1 2\n3\n", &default_settings()).unwrap_err();
+        assert_eq!(error.to_string(), "row at line 4 has 1 numbers, expected 2");
+    }
 }
@@ -0,0 +1,196 @@
+use peg::error::ParseError;
+use peg::str::LineCol;
+use std::fmt::{Display, Error, Formatter};
+
+peg::parser! {
+    grammar bingo_parser() for str {
+        rule space() = [' ' | '\t']
+        rule eol() = space()* "\r"? "\n"
+        rule number() -> u64
+            = n:$(['0'..='9']+) {? n.parse().or(Err("a number that fits in 64 bits")) }
+
+        rule draws() -> Vec<u64> = space()* d:(number() ++ (space()* "," space()*)) space()* { d }
+        rule row() -> Vec<u64> = space()* r:(number() ++ (space()+)) space()* { r }
+        rule board() -> (usize, Vec<Vec<u64>>) = p:position!() rows:(row() ++ eol()) { (p, rows) }
+
+        pub rule file() -> (Vec<u64>, Vec<(usize, Vec<Vec<u64>>)>)
+            = eol()* d:draws() eol()+ b:(board() ** (eol() eol()+)) eol()* space()* ![_] { (d, b) }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InputError {
+    Syntax(ParseError<LineCol>),
+    // lines are 1-based, like the ones reported by peg
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    NotSquare {
+        line: usize,
+        rows: usize,
+        columns: usize,
+    },
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            InputError::Syntax(e) => write!(f, "{}", e),
+            InputError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "row at line {} has {} numbers, expected {}",
+                line, found, expected
+            ),
+            InputError::NotSquare {
+                line,
+                rows,
+                columns,
+            } => write!(
+                f,
+                "board at line {} has {} rows of {} numbers",
+                line, rows, columns
+            ),
+        }
+    }
+}
+
+impl From<ParseError<LineCol>> for InputError {
+    fn from(e: ParseError<LineCol>) -> InputError {
+        InputError::Syntax(e)
+    }
+}
+
+// rows of a board are on consecutive lines, so the line of the first one is enough to locate them
+fn check_board(line: usize, rows: Vec<Vec<u64>>) -> Result<Vec<u64>, InputError> {
+    let columns = rows[0].len();
+
+    if let Some((idx, row)) = rows.iter().enumerate().find(|(_, r)| r.len() != columns) {
+        return Err(InputError::RaggedRow {
+            line: line + idx,
+            expected: columns,
+            found: row.len(),
+        });
+    }
+
+    if rows.len() != columns {
+        return Err(InputError::NotSquare {
+            line,
+            rows: rows.len(),
+            columns,
+        });
+    }
+
+    Ok(rows.concat())
+}
+
+// tolerates CRLF, extra blank lines and trailing whitespace
+pub fn parse(input: &str) -> Result<(Vec<u64>, Vec<Vec<u64>>), InputError> {
+    let (draws, boards) = bingo_parser::file(input)?;
+
+    // boards come in order, so each one only counts the lines since the previous one
+    let (mut line, mut scanned) = (1, 0);
+    let boards = boards
+        .into_iter()
+        .map(|(position, rows)| {
+            line += input[scanned..position].matches('\n').count();
+            scanned = position;
+            check_board(line, rows)
+        })
+        .collect::<Result<Vec<Vec<u64>>, InputError>>()?;
+
+    Ok((draws, boards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syntax_error(input: &str) -> ParseError<LineCol> {
+        match parse(input) {
+            Err(InputError::Syntax(e)) => e,
+            other => panic!("expected a syntax error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let (draws, boards) = parse("7,4,9\n\n 1  2\n 3  4\n\n5 6\n7 8\n").unwrap();
+        assert_eq!(draws, vec![7, 4, 9]);
+        assert_eq!(boards, vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let input = "\r\n7, 4,9 \r\n\r\n\r\n 1  2 \r\n 3  4\r\n\r\n  \r\n\r\n5 6\r\n7 8";
+        let (draws, boards) = parse(input).unwrap();
+        assert_eq!(draws, vec![7, 4, 9]);
+        assert_eq!(boards, vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+    }
+
+    #[test]
+    fn test_malformed_number() {
+        let error = syntax_error("7,4,9\n\n1 2\n3 x\n");
+        assert_eq!(error.location.line, 4);
+        assert_eq!(error.location.column, 3);
+    }
+
+    #[test]
+    fn test_number_too_big() {
+        let error = syntax_error("7,99999999999999999999\n\n1\n");
+        assert_eq!(error.location.line, 1);
+        assert!(error.expected.tokens().any(|t| t.contains("64 bits")));
+    }
+
+    #[test]
+    fn test_ragged_row() {
+        assert_eq!(
+            parse("7,4,9\n\n1 2\n3 4\n\n5 6\n7\n"),
+            Err(InputError::RaggedRow {
+                line: 7,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_rows() {
+        assert_eq!(
+            parse("7,4,9\n\n\n1 2\n\n5 6\n7 8\n"),
+            Err(InputError::NotSquare {
+                line: 4,
+                rows: 1,
+                columns: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_many_boards() {
+        let boards = 50_000;
+        let input = format!("1,2\n{}\n1 2\n", "\n1 2\n3 4\n".repeat(boards));
+        assert_eq!(
+            parse(&input),
+            Err(InputError::NotSquare {
+                line: 3 * boards + 3,
+                rows: 1,
+                columns: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let input = std::fs::read_to_string("input").unwrap();
+        let (draws, boards) = parse(&input).expect("Unable to parse");
+        assert_eq!(draws.len(), 100);
+        assert_eq!(boards.len(), 100);
+        assert!(boards.iter().all(|b| b.len() == 25));
+    }
+}
@@ -0,0 +1,85 @@
+// only the counters and the map are measured, most of the shared code goes unused
+#![allow(dead_code)]
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+mod counter;
+#[cfg(test)]
+mod fixtures;
+// the tests of these modules are left out of the benchmark, but not the imports of their
+// test modules
+#[allow(unused_imports)]
+mod line;
+#[allow(unused_imports)]
+mod parser;
+#[allow(unused_imports)]
+mod vents;
+use counter::{OverlapCounter, Sparse};
+use line::{Line, Point};
+use vents::VentMap;
+
+// deterministic pseudo-random horizontal, vertical and diagonal lines in 0..size
+fn generate_lines(count: usize, size: i64) -> Vec<Line> {
+    let mut state: u64 = 0x2021_0005;
+    let mut next = |bound: i64| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((state >> 33) % bound as u64) as i64
+    };
+
+    (0..count)
+        .map(|i| {
+            let start = Point::new(next(size), next(size));
+            let length = next(size / 4) + 1;
+            let end = match i % 3 {
+                0 => Point::new((start.x + length).min(size - 1), start.y),
+                1 => Point::new(start.x, (start.y + length).min(size - 1)),
+                _ => {
+                    let length = length.min(size - 1 - start.x).min(size - 1 - start.y);
+                    Point::new(start.x + length, start.y + length)
+                }
+            };
+            Line::new(start, end)
+        })
+        .collect()
+}
+
+fn dense(lines: &[Line]) -> Box<dyn OverlapCounter> {
+    Box::new(counter::dense_for(lines).unwrap())
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let input = std::fs::read_to_string("input").expect("Could not read input");
+    let puzzle = parser::lines_parser::file(&input).expect("Error parsing input");
+
+    let mut group = c.benchmark_group("overlaps");
+    let inputs = [
+        ("puzzle input".to_string(), puzzle),
+        (
+            "2000 lines in 0..1000".to_string(),
+            generate_lines(2000, 1000),
+        ),
+        (
+            "2000 lines in 0..4000".to_string(),
+            generate_lines(2000, 4000),
+        ),
+    ];
+
+    for (name, lines) in inputs.iter() {
+        group.bench_with_input(BenchmarkId::new("sparse", name), lines, |b, lines| {
+            b.iter(|| {
+                VentMap::with_counter(lines, Box::new(Sparse::default()))
+                    .at_least(2)
+                    .len()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("dense", name), lines, |b, lines| {
+            b.iter(|| VentMap::with_counter(lines, dense(lines)).at_least(2).len())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
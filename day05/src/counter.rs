@@ -0,0 +1,214 @@
+use crate::line::{Line, Point};
+use std::collections::HashMap;
+
+// above this many cells (32MB of counters) the dense grid is not worth it
+pub const DENSE_LIMIT: usize = 1 << 24;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Backend {
+    Sparse,
+    Dense,
+}
+
+pub trait OverlapCounter {
+    fn backend(&self) -> Backend;
+    fn add(&mut self, point: Point);
+    fn count_at(&self, point: Point) -> usize;
+    // every covered point with its count, in no particular order
+    fn counts(&self) -> Box<dyn Iterator<Item = (Point, usize)> + '_>;
+}
+
+#[derive(Default)]
+pub struct Sparse {
+    counts: HashMap<Point, usize>,
+}
+
+impl OverlapCounter for Sparse {
+    fn backend(&self) -> Backend {
+        Backend::Sparse
+    }
+
+    fn add(&mut self, point: Point) {
+        *self.counts.entry(point).or_insert(0) += 1;
+    }
+
+    fn count_at(&self, point: Point) -> usize {
+        self.counts.get(&point).copied().unwrap_or(0)
+    }
+
+    fn counts(&self) -> Box<dyn Iterator<Item = (Point, usize)> + '_> {
+        Box::new(self.counts.iter().map(|(&p, &v)| (p, v)))
+    }
+}
+
+// flat row-major grid over a fixed box, points outside of it can't be added
+pub struct Dense {
+    min: Point,
+    width: usize,
+    height: usize,
+    counts: Vec<u16>,
+}
+
+impl Dense {
+    pub fn new(min: Point, max: Point) -> Dense {
+        let width = (max.x - min.x + 1) as usize;
+        let height = (max.y - min.y + 1) as usize;
+        Dense {
+            min,
+            width,
+            height,
+            counts: vec![0; width * height],
+        }
+    }
+
+    fn index(&self, point: Point) -> Option<usize> {
+        let (x, y) = (point.x - self.min.x, point.y - self.min.y);
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        Some(y as usize * self.width + x as usize)
+    }
+}
+
+impl OverlapCounter for Dense {
+    fn backend(&self) -> Backend {
+        Backend::Dense
+    }
+
+    fn add(&mut self, point: Point) {
+        let index = self.index(point).expect("Point outside of the dense grid");
+        self.counts[index] += 1;
+    }
+
+    fn count_at(&self, point: Point) -> usize {
+        self.index(point).map_or(0, |i| self.counts[i] as usize)
+    }
+
+    fn counts(&self) -> Box<dyn Iterator<Item = (Point, usize)> + '_> {
+        Box::new(
+            self.counts
+                .iter()
+                .enumerate()
+                .filter(|(_, &v)| v > 0)
+                .map(move |(i, &v)| {
+                    let x = self.min.x + (i % self.width) as i64;
+                    let y = self.min.y + (i / self.width) as i64;
+                    (Point::new(x, y), v as usize)
+                }),
+        )
+    }
+}
+
+// the coverage of a line never leaves the box of its endpoints
+pub fn bounding_box<'a>(lines: impl IntoIterator<Item = &'a Line>) -> Option<(Point, Point)> {
+    lines
+        .into_iter()
+        .flat_map(|l| [l.start, l.end])
+        .fold(None, |bounds, p| match bounds {
+            None => Some((p, p)),
+            Some((min, max)) => Some((
+                Point::new(min.x.min(p.x), min.y.min(p.y)),
+                Point::new(max.x.max(p.x), max.y.max(p.y)),
+            )),
+        })
+}
+
+// a grid over the box of the lines, as long as the u16 counters can't overflow,
+// which they could only do with more lines than that
+pub fn dense_for<'a, I>(lines: I) -> Option<Dense>
+where
+    I: IntoIterator<Item = &'a Line> + Clone,
+{
+    if lines.clone().into_iter().count() > u16::MAX as usize {
+        return None;
+    }
+    let origin = Point::new(0, 0);
+    let (min, max) = bounding_box(lines).unwrap_or((origin, origin));
+    Some(Dense::new(min, max))
+}
+
+// at most DENSE_LIMIT points in the bounding box, false when there are no lines
+pub fn small_box<'a, I>(lines: I) -> bool
+where
+    I: IntoIterator<Item = &'a Line>,
+{
+    bounding_box(lines).is_some_and(|(min, max)| {
+        ((max.x as i128 - min.x as i128 + 1) as u128)
+            * ((max.y as i128 - min.y as i128 + 1) as u128)
+            <= DENSE_LIMIT as u128
+    })
+}
+
+// dense when the box is small enough and the u16 counters can't overflow
+pub fn for_lines<'a, I>(lines: I) -> Box<dyn OverlapCounter>
+where
+    I: IntoIterator<Item = &'a Line> + Clone,
+{
+    let small = small_box(lines.clone());
+    match small.then(|| dense_for(lines)).flatten() {
+        Some(dense) => Box::new(dense),
+        None => Box::new(Sparse::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x1: i64, y1: i64, x2: i64, y2: i64) -> Line {
+        Line::new(Point::new(x1, y1), Point::new(x2, y2))
+    }
+
+    fn fill(counter: &mut dyn OverlapCounter) {
+        [Point::new(-2, 3), Point::new(4, 5), Point::new(-2, 3)]
+            .into_iter()
+            .for_each(|p| counter.add(p));
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let mut sparse = Sparse::default();
+        let mut dense = Dense::new(Point::new(-2, 3), Point::new(4, 5));
+        fill(&mut sparse);
+        fill(&mut dense);
+
+        for counter in [&sparse as &dyn OverlapCounter, &dense] {
+            assert_eq!(counter.count_at(Point::new(-2, 3)), 2);
+            assert_eq!(counter.count_at(Point::new(4, 5)), 1);
+            assert_eq!(counter.count_at(Point::new(0, 4)), 0);
+            assert_eq!(counter.count_at(Point::new(100, 100)), 0);
+
+            let mut counts: Vec<(Point, usize)> = counter.counts().collect();
+            counts.sort_unstable_by_key(|(p, _)| (p.x, p.y));
+            assert_eq!(counts, vec![(Point::new(-2, 3), 2), (Point::new(4, 5), 1)]);
+        }
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let lines = [line(5, 1, 0, 7), line(-3, 2, 2, 2)];
+        assert_eq!(
+            bounding_box(&lines),
+            Some((Point::new(-3, 1), Point::new(5, 7)))
+        );
+        assert_eq!(bounding_box(&[] as &[Line]), None);
+    }
+
+    #[test]
+    fn test_auto_selection() {
+        assert_eq!(for_lines(&[line(0, 0, 999, 999)]).backend(), Backend::Dense);
+        assert_eq!(
+            for_lines(&[line(0, 0, 1_000_000, 1_000_000)]).backend(),
+            Backend::Sparse
+        );
+        assert_eq!(for_lines(&[] as &[Line]).backend(), Backend::Sparse);
+    }
+
+    #[test]
+    fn test_too_many_lines_for_dense() {
+        let lines = vec![line(0, 0, 1, 1); u16::MAX as usize + 1];
+        assert_eq!(for_lines(&lines).backend(), Backend::Sparse);
+        assert!(dense_for(&lines).is_none());
+        assert!(dense_for(&lines[1..]).is_some());
+    }
+}
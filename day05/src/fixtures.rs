@@ -0,0 +1,11 @@
+use crate::line::Line;
+use crate::parser;
+
+pub fn lines(input: &str) -> Vec<Line> {
+    parser::lines_parser::file(input).unwrap()
+}
+
+// the example from the puzzle
+pub fn example() -> Vec<Line> {
+    lines("0,9 -> 5,9\n8,0 -> 0,8\n9,4 -> 3,4\n2,2 -> 2,1\n7,0 -> 7,4\n6,4 -> 2,0\n0,9 -> 2,9\n3,4 -> 1,4\n0,0 -> 8,8\n5,5 -> 8,2")
+}
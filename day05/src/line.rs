@@ -1,3 +1,7 @@
+use std::ops::Not;
+use std::str::FromStr;
+
+#[derive(PartialEq, Clone, Debug)]
 pub struct Line {
     pub start: Point,
     pub end: Point,
@@ -35,78 +39,254 @@ impl Line {
         self.start.x == self.end.x
     }
 
-    pub fn coverage(&self) -> Vec<Point> {
-        let dx = (self.end.x - self.start.x).signum();
-        let dy = (self.end.y - self.start.y).signum();
+    // a single point is horizontal and vertical, but not diagonal
+    pub fn is_diagonal(&self) -> bool {
+        let dx = (self.end.x - self.start.x).abs();
+        dx != 0 && dx == (self.end.y - self.start.y).abs()
+    }
+
+    // integer Bresenham, so any slope works and 45° lines still cover every diagonal step
+    pub fn coverage(&self) -> Coverage {
+        let dx = (self.end.x - self.start.x).abs();
+        let dy = -(self.end.y - self.start.y).abs();
+
+        Coverage {
+            next: Some(self.start),
+            end: self.end,
+            dx,
+            dy,
+            sx: (self.end.x - self.start.x).signum(),
+            sy: (self.end.y - self.start.y).signum(),
+            error: dx + dy,
+        }
+    }
+}
+
+pub struct Coverage {
+    next: Option<Point>,
+    end: Point,
+    dx: i64,
+    dy: i64,
+    sx: i64,
+    sy: i64,
+    error: i64,
+}
+
+impl Iterator for Coverage {
+    type Item = Point;
 
-        let mut tracer = Point::new(self.start.x, self.start.y);
-        let mut output = vec![tracer];
-        while tracer != self.end {
-            tracer = tracer.offset(dx, dy);
-            output.push(tracer);
+    fn next(&mut self) -> Option<Point> {
+        let current = self.next?;
+        if current == self.end {
+            self.next = None;
+            return Some(current);
         }
 
-        output
+        let mut step = (0, 0);
+        let doubled = 2 * self.error;
+        if doubled >= self.dy {
+            self.error += self.dy;
+            step.0 = self.sx;
+        }
+        if doubled <= self.dx {
+            self.error += self.dx;
+            step.1 = self.sy;
+        }
+
+        self.next = Some(current.offset(step.0, step.1));
+        Some(current)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Filter {
+    Horizontal,
+    Vertical,
+    Diagonal,
+    Any(Vec<Filter>),
+    All(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn matches(&self, line: &Line) -> bool {
+        match self {
+            Filter::Horizontal => line.is_horizontal(),
+            Filter::Vertical => line.is_vertical(),
+            Filter::Diagonal => line.is_diagonal(),
+            Filter::Any(filters) => filters.iter().any(|f| f.matches(line)),
+            Filter::All(filters) => filters.iter().all(|f| f.matches(line)),
+            Filter::Not(filter) => !filter.matches(line),
+        }
+    }
+
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Any(mut filters) => {
+                filters.push(other);
+                Filter::Any(filters)
+            }
+            _ => Filter::Any(vec![self, other]),
+        }
+    }
+
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::All(mut filters) => {
+                filters.push(other);
+                Filter::All(filters)
+            }
+            _ => Filter::All(vec![self, other]),
+        }
+    }
+
+    // comma separated kinds, e.g. `horizontal,diagonal`
+    pub fn any_of(kinds: &str) -> Result<Filter, String> {
+        kinds
+            .split(',')
+            .map(|kind| kind.parse())
+            .collect::<Result<Vec<Filter>, String>>()
+            .map(Filter::Any)
+    }
+
+    pub fn select<'a>(&'a self, lines: &'a [Line]) -> impl Iterator<Item = &'a Line> + Clone {
+        lines.iter().filter(move |l| self.matches(l))
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Filter, String> {
+        match s {
+            "horizontal" => Ok(Filter::Horizontal),
+            "vertical" => Ok(Filter::Vertical),
+            "diagonal" => Ok(Filter::Diagonal),
+            _ => Err(format!("unknown line kind {}", s)),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::parser;
 
     #[test]
     fn test_coverage() {
         let line = parser::lines_parser::line("0,9 -> 5,9").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 6)
+        assert_eq!(cov.count(), 6)
     }
 
     #[test]
     fn test_coverage_reversed() {
         let line = parser::lines_parser::line("5,9 -> 0,9").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 6)
+        assert_eq!(cov.count(), 6)
     }
 
     #[test]
     fn test_coverage_vertical() {
         let line = parser::lines_parser::line("2,4 -> 2,9").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 6)
+        assert_eq!(cov.count(), 6)
     }
 
     #[test]
     fn test_coverage_vertical_reversed() {
         let line = parser::lines_parser::line("2,9 -> 2,4").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 6)
+        assert_eq!(cov.count(), 6)
     }
 
     #[test]
     fn test_coverage_diagonal() {
         let line = parser::lines_parser::line("1,1 -> 5,5").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 5)
+        assert_eq!(cov.count(), 5)
     }
 
     #[test]
     fn test_coverage_diagonal_2() {
         let line = parser::lines_parser::line("5,5 -> 1,1").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 5)
+        assert_eq!(cov.count(), 5)
     }
 
     #[test]
     fn test_coverage_diagonal_3() {
         let line = parser::lines_parser::line("1,5 -> 5,1").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 5)
+        assert_eq!(cov.count(), 5)
     }
 
     #[test]
     fn test_coverage_diagonal_4() {
         let line = parser::lines_parser::line("5,1 -> 1,5").unwrap();
         let cov = line.coverage();
-        assert_eq!(cov.len(), 5)
+        assert_eq!(cov.count(), 5)
+    }
+
+    #[test]
+    fn test_coverage_single_point() {
+        let line = parser::lines_parser::line("3,3 -> 3,3").unwrap();
+        assert_eq!(
+            line.coverage().collect::<Vec<Point>>(),
+            vec![Point::new(3, 3)]
+        );
+    }
+
+    #[test]
+    fn test_coverage_steep_slope() {
+        let line = parser::lines_parser::line("0,0 -> 2,5").unwrap();
+        let cov: Vec<Point> = line.coverage().collect();
+        assert_eq!(cov.len(), 6);
+        assert_eq!(cov.first(), Some(&Point::new(0, 0)));
+        assert_eq!(cov.last(), Some(&Point::new(2, 5)));
+        assert!(cov.windows(2).all(|w| w[1].y == w[0].y + 1));
+    }
+
+    #[test]
+    fn test_coverage_shallow_slope_reversed() {
+        let line = parser::lines_parser::line("7,1 -> 0,4").unwrap();
+        let cov: Vec<Point> = line.coverage().collect();
+        assert_eq!(cov.len(), 8);
+        assert_eq!(cov.last(), Some(&Point::new(0, 4)));
+        assert!(cov.windows(2).all(|w| w[1].x == w[0].x - 1));
+    }
+
+    #[test]
+    fn test_is_diagonal() {
+        let line = |s| parser::lines_parser::line(s).unwrap();
+        assert!(line("1,5 -> 5,1").is_diagonal());
+        assert!(!line("0,9 -> 5,9").is_diagonal());
+        assert!(!line("0,0 -> 2,5").is_diagonal());
+        assert!(!line("3,3 -> 3,3").is_diagonal());
+    }
+
+    #[test]
+    fn test_filters() {
+        let lines =
+            parser::lines_parser::file("0,9 -> 5,9\n2,4 -> 2,9\n1,1 -> 5,5\n0,0 -> 2,5").unwrap();
+        let count = |f: Filter| f.select(&lines).count();
+
+        assert_eq!(count(Filter::Horizontal.or(Filter::Vertical)), 2);
+        assert_eq!(
+            count(Filter::Horizontal.or(Filter::Vertical).or(Filter::Diagonal)),
+            3
+        );
+        assert_eq!(count(!Filter::Horizontal.or(Filter::Vertical)), 2);
+        assert_eq!(count((!Filter::Diagonal).and(!Filter::Vertical)), 2);
+        assert_eq!(count(Filter::any_of("diagonal,vertical").unwrap()), 2);
+        assert!(Filter::any_of("diagonal,sideways").is_err());
     }
 }
@@ -1,33 +1,139 @@
+mod counter;
+#[cfg(test)]
+mod fixtures;
 mod line;
+mod overlap;
 mod parser;
+mod render;
+mod vents;
 
-use line::Line;
-use line::Point;
-use std::collections::HashMap;
+use counter::Sparse;
+use line::{Filter, Line, Point};
+use overlap::UnsupportedSlope;
+use render::RenderError;
+use vents::VentMap;
 
-fn results<'a, I>(lines: I) -> usize
+fn main() {
+    let input = std::fs::read_to_string("input").expect("Could not read input");
+    let lines = parser::lines_parser::file(&input).expect("Error parsing input");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let raster = args.iter().any(|arg| arg == "--raster");
+    let straight = Filter::Horizontal.or(Filter::Vertical);
+    let results = solve(straight.select(&lines), raster)
+        .and_then(|filtered| Ok((filtered, solve(&lines, raster)?)));
+    match results {
+        Ok((filtered_results, full_results)) => {
+            println!("Filtered results: {}", filtered_results);
+            println!("Full results: {}", full_results);
+        }
+        Err(e) => {
+            eprintln!("Unable to count the overlaps: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // `--at-least=k` and `--at=x,y` query the map of the lines kept by `--only=` and `--skip=`
+    // (e.g. `--only=horizontal,vertical --skip=diagonal`), `--stats` prints the hottest
+    // points and the overlap histogram, `--ascii` prints the map for small inputs
+    // and `--export` writes it as a heatmap and the lines as an SVG.
+    // `--counter=sparse` or `--counter=dense` force the backend of the map
+    let mut filter = Filter::All(vec![]);
+    let mut backend = None;
+    for arg in &args {
+        if let Some(kinds) = arg.strip_prefix("--only=") {
+            filter = filter.and(Filter::any_of(kinds).expect("Invalid filter"));
+        } else if let Some(kinds) = arg.strip_prefix("--skip=") {
+            filter = filter.and(!Filter::any_of(kinds).expect("Invalid filter"));
+        } else if let Some(name) = arg.strip_prefix("--counter=") {
+            backend = Some(name);
+        }
+    }
+
+    // the map rasterizes the lines, so it's only built when asked for
+    let map_args = ["--at-least=", "--at=", "--ascii", "--export", "--stats"];
+    if !args
+        .iter()
+        .any(|arg| map_args.iter().any(|m| arg.starts_with(m)))
+    {
+        return;
+    }
+    let selected = filter.select(&lines);
+    let map = build_map(selected.clone(), backend);
+    for arg in &args {
+        if let Some(k) = arg.strip_prefix("--at-least=") {
+            let k = k.parse().expect("Invalid threshold");
+            println!(
+                "Points with at least {} lines: {}",
+                k,
+                map.at_least(k).len()
+            );
+        } else if let Some(point) = arg.strip_prefix("--at=") {
+            let point = parse_point(point);
+            println!("Lines at {},{}: {}", point.x, point.y, map.count_at(point));
+        } else if arg == "--ascii" {
+            match render::to_ascii(&map) {
+                Some(ascii) => print!("{}", ascii),
+                None => println!("Map too large to print"),
+            }
+        } else if arg == "--export" {
+            if let Err(e) = export(&map, selected.clone()) {
+                println!("Unable to export the map: {}", e);
+            }
+        } else if arg == "--stats" {
+            println!("Counter: {:?}", map.backend());
+            let (max, points) = map.hottest();
+            println!("Hottest points ({} lines): {:?}", max, points);
+            for (count, points) in map.histogram() {
+                println!("{} lines: {} points", count, points);
+            }
+        }
+    }
+}
+
+// `--raster` always counts by rasterizing the lines, otherwise they're only rasterized
+// when some line isn't horizontal, vertical or diagonal
+fn solve<'a, I>(lines: I, raster: bool) -> Result<u128, UnsupportedSlope>
 where
-    I: Iterator<Item = &'a Line>,
+    I: IntoIterator<Item = &'a Line> + Clone,
 {
-    let mut results_map: HashMap<Point, usize> = HashMap::new();
-    lines.flat_map(Line::coverage).for_each(|p| {
-        let count = results_map.entry(p).or_insert(0);
-        *count += 1;
-    });
-    results_map.iter().filter(|(_, &v)| v > 1).count()
+    if raster {
+        Ok(VentMap::new(lines).at_least(2).len() as u128)
+    } else {
+        overlap::count(lines)
+    }
 }
 
-fn main() {
-    let input = std::fs::read_to_string("input").expect("Could not read input");
-    let lines = parser::lines_parser::file(&input).expect("Error parsing input");
+fn build_map<'a, I>(lines: I, backend: Option<&str>) -> VentMap
+where
+    I: IntoIterator<Item = &'a Line> + Clone,
+{
+    match backend {
+        None => VentMap::new(lines),
+        Some("sparse") => VentMap::with_counter(lines, Box::new(Sparse::default())),
+        Some("dense") => {
+            let dense =
+                counter::dense_for(lines.clone()).expect("Too many lines for a dense counter");
+            VentMap::with_counter(lines, Box::new(dense))
+        }
+        Some(name) => panic!("Unknown counter {}", name),
+    }
+}
 
-    let filtered_results = results(
-        lines
-            .iter()
-            .filter(|line| line.is_horizontal() || line.is_vertical()),
-    );
-    let full_results = results(lines.iter());
+// the SVG is always written, the heatmaps only when they fit in memory
+fn export<'a, I>(map: &VentMap, lines: I) -> Result<(), RenderError>
+where
+    I: IntoIterator<Item = &'a Line> + Clone,
+{
+    std::fs::write("vents.svg", render::to_svg(lines)).expect("Unable to write SVG");
+    std::fs::write("heatmap.pgm", render::to_pgm(map)?).expect("Unable to write PGM");
+    std::fs::write("heatmap.png", render::to_png(map)?).expect("Unable to write PNG");
+    Ok(())
+}
 
-    println!("Filtered results: {}", filtered_results);
-    println!("Full results: {}", full_results);
+fn parse_point(point: &str) -> Point {
+    match point.split_once(',').map(|(x, y)| (x.parse(), y.parse())) {
+        Some((Ok(x), Ok(y))) => Point::new(x, y),
+        _ => panic!("Expected --at=x,y"),
+    }
 }
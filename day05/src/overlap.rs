@@ -0,0 +1,286 @@
+use crate::counter;
+use crate::line::{Line, Point};
+use crate::vents::VentMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Error, Formatter};
+
+// only horizontal, vertical and 45° lines cover exactly the lattice points on them,
+// other slopes are rasterized and can't be counted analytically
+#[derive(Debug, PartialEq)]
+pub struct UnsupportedSlope(pub Line);
+
+impl Display for UnsupportedSlope {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let Line { start, end } = &self.0;
+        write!(
+            f,
+            "{},{} -> {},{} is not horizontal, vertical or diagonal, \
+             and the lines are too far apart to be rasterized",
+            start.x, start.y, end.x, end.y
+        )
+    }
+}
+
+// supporting line of a segment: the direction and the offset from the origin
+type Support = ((i128, i128), i128);
+
+// a segment as start + t * direction for t in 0..=steps, with a primitive direction
+// normalized to point right (or up when vertical), so collinear segments share it
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: (i128, i128),
+    direction: (i128, i128),
+    steps: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn cross(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+impl Segment {
+    fn new(line: &Line) -> Segment {
+        let (mut start, mut end) = (line.start, line.end);
+        if (end.x, end.y) < (start.x, start.y) {
+            std::mem::swap(&mut start, &mut end);
+        }
+
+        let (dx, dy) = (
+            end.x as i128 - start.x as i128,
+            end.y as i128 - start.y as i128,
+        );
+        let steps = gcd(dx, dy);
+        // a single point is a horizontal segment with no steps
+        let direction = if steps == 0 {
+            (1, 0)
+        } else {
+            (dx / steps, dy / steps)
+        };
+
+        Segment {
+            start: (start.x as i128, start.y as i128),
+            direction,
+            steps,
+        }
+    }
+
+    fn support(&self) -> Support {
+        (self.direction, cross(self.direction, self.start))
+    }
+
+    // position of the start along the supporting line, in steps from the line's own origin
+    fn offset(&self) -> i128 {
+        let (a, b) = self.direction;
+        let k = self.start.0 * a + self.start.1 * b;
+        k.div_euclid(a * a + b * b)
+    }
+
+    fn point(&self, t: i128) -> Point {
+        Point::new(
+            (self.start.0 + t * self.direction.0) as i64,
+            (self.start.1 + t * self.direction.1) as i64,
+        )
+    }
+
+    // the lattice point where two non-parallel segments meet, if any
+    fn crossing(&self, other: &Segment) -> Option<(i128, i128)> {
+        let denominator = cross(self.direction, other.direction);
+        let delta = (other.start.0 - self.start.0, other.start.1 - self.start.1);
+        let t = cross(delta, other.direction);
+        let u = cross(delta, self.direction);
+
+        if t % denominator != 0 || u % denominator != 0 {
+            return None;
+        }
+        let (t, u) = (t / denominator, u / denominator);
+        ((0..=self.steps).contains(&t) && (0..=other.steps).contains(&u)).then_some((t, u))
+    }
+}
+
+// ranges of positions along a line covered by at least two of the given ranges
+fn covered_twice(ranges: &[(i128, i128)]) -> Vec<(i128, i128)> {
+    let mut events: Vec<(i128, i64)> = ranges
+        .iter()
+        .flat_map(|&(lo, hi)| [(lo, 1), (hi + 1, -1)])
+        .collect();
+    events.sort_unstable();
+
+    let mut output = vec![];
+    let mut depth = 0;
+    let mut opened = 0;
+    for (position, change) in events {
+        let before = depth;
+        depth += change;
+        if before < 2 && depth >= 2 {
+            opened = position;
+        } else if before >= 2 && depth < 2 && position > opened {
+            output.push((opened, position - 1));
+        }
+    }
+    output
+}
+
+// counts the points covered by at least two lines without rasterizing them:
+// collinear lines are merged as ranges along their common support and crossing lines
+// are intersected pairwise
+pub fn overlaps<'a, I>(lines: I) -> Result<u128, UnsupportedSlope>
+where
+    I: Iterator<Item = &'a Line>,
+{
+    let segments = lines
+        .map(|line| {
+            if line.is_horizontal() || line.is_vertical() || line.is_diagonal() {
+                Ok(Segment::new(line))
+            } else {
+                Err(UnsupportedSlope(line.clone()))
+            }
+        })
+        .collect::<Result<Vec<Segment>, UnsupportedSlope>>()?;
+
+    let mut supports: HashMap<Support, Vec<(i128, i128)>> = HashMap::new();
+    for segment in &segments {
+        let lo = segment.offset();
+        supports
+            .entry(segment.support())
+            .or_default()
+            .push((lo, lo + segment.steps));
+    }
+    let doubles: HashMap<Support, Vec<(i128, i128)>> = supports
+        .iter()
+        .map(|(&support, ranges)| (support, covered_twice(ranges)))
+        .filter(|(_, ranges)| !ranges.is_empty())
+        .collect();
+
+    let in_doubles = |segment: &Segment, t: i128| {
+        let position = segment.offset() + t;
+        doubles
+            .get(&segment.support())
+            .is_some_and(|ranges| ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&position)))
+    };
+
+    // every point inside the collinear overlaps of several supports is also a crossing,
+    // so the crossings tell how many times the collinear ranges count each point
+    let mut crossings: HashMap<Point, HashSet<Support>> = HashMap::new();
+    for (i, a) in segments.iter().enumerate() {
+        for b in &segments[i + 1..] {
+            if a.direction == b.direction {
+                continue;
+            }
+            if let Some((t, u)) = a.crossing(b) {
+                let supports = crossings.entry(a.point(t)).or_default();
+                for (segment, position) in [(a, t), (b, u)] {
+                    if in_doubles(segment, position) {
+                        supports.insert(segment.support());
+                    }
+                }
+            }
+        }
+    }
+
+    let collinear: i128 = doubles.values().flatten().map(|(lo, hi)| hi - lo + 1).sum();
+    let crossing: i128 = crossings
+        .values()
+        .map(|supports| 1 - supports.len() as i128)
+        .sum();
+
+    Ok((collinear + crossing) as u128)
+}
+
+// analytic when every line allows it, otherwise rasterized if the lines fit in a small box
+pub fn count<'a, I>(lines: I) -> Result<u128, UnsupportedSlope>
+where
+    I: IntoIterator<Item = &'a Line> + Clone,
+{
+    match overlaps(lines.clone().into_iter()) {
+        Err(_) if counter::small_box(lines.clone()) => {
+            Ok(VentMap::new(lines).at_least(2).len() as u128)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{example, lines};
+
+    #[test]
+    fn test_example() {
+        let lines = example();
+        let straight = lines
+            .iter()
+            .filter(|l| l.is_horizontal() || l.is_vertical());
+        assert_eq!(overlaps(straight), Ok(5));
+        assert_eq!(overlaps(lines.iter()), Ok(12));
+    }
+
+    #[test]
+    fn test_collinear_and_crossing_at_same_point() {
+        // the crossing at 3,0 is inside the collinear overlap 2,0 -> 4,0
+        let lines = lines("0,0 -> 4,0\n2,0 -> 6,0\n3,3 -> 3,0");
+        assert_eq!(overlaps(lines.iter()), Ok(3));
+    }
+
+    #[test]
+    fn test_collinear_overlaps_crossing() {
+        // the horizontal and vertical overlaps share 2,2
+        let lines = lines("0,2 -> 4,2\n1,2 -> 3,2\n2,0 -> 2,4\n2,1 -> 2,3");
+        assert_eq!(overlaps(lines.iter()), Ok(5));
+    }
+
+    #[test]
+    fn test_non_lattice_crossing() {
+        // these diagonals cross at 0.5,0.5
+        let lines = lines("0,0 -> 1,1\n0,1 -> 1,0");
+        assert_eq!(overlaps(lines.iter()), Ok(0));
+    }
+
+    #[test]
+    fn test_other_slopes_are_rasterized() {
+        // 0,0 -> 4,6 covers 7 points, 2,3 and 4,6 among them
+        let lines = lines("0,0 -> 4,6\n6,9 -> 2,3\n0,2 -> 4,2");
+        assert_eq!(
+            overlaps(lines.iter()),
+            Err(UnsupportedSlope(lines[0].clone()))
+        );
+        assert_eq!(count(&lines), Ok(5));
+        assert_eq!(
+            count(&lines),
+            Ok(VentMap::new(&lines).at_least(2).len() as u128)
+        );
+    }
+
+    #[test]
+    fn test_other_slopes_too_large() {
+        let lines = lines("0,0 -> 4000000,6000000\n0,2 -> 4,2");
+        assert_eq!(count(&lines), Err(UnsupportedSlope(lines[0].clone())));
+    }
+
+    #[test]
+    fn test_huge_coordinates() {
+        let line = |x1, y1, x2, y2| Line::new(Point::new(x1, y1), Point::new(x2, y2));
+        let lines = [
+            line(0, 0, 4_000_000_000_000, 0),
+            line(1_000_000_000_000, 0, 9_000_000_000_000, 0),
+            line(2_000_000_000_000, -5, 2_000_000_000_000, 5),
+        ];
+        assert_eq!(overlaps(lines.iter()), Ok(3_000_000_000_001));
+    }
+
+    #[test]
+    fn test_matches_rasterization() {
+        let input = std::fs::read_to_string("input").unwrap();
+        let lines = lines(&input);
+        assert_eq!(
+            overlaps(lines.iter()),
+            Ok(VentMap::new(&lines).at_least(2).len() as u128)
+        );
+    }
+}
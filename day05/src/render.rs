@@ -0,0 +1,239 @@
+use crate::counter;
+use crate::line::{Line, Point};
+use crate::vents::VentMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Error, Formatter};
+
+// bigger maps are not worth printing
+pub const ASCII_LIMIT: i64 = 100;
+// one byte per pixel, so at most 64MB of heatmap
+pub const IMAGE_LIMIT: u128 = 1 << 26;
+const SVG_SIZE: f64 = 800.0;
+
+#[derive(Debug)]
+pub enum RenderError {
+    TooLarge { width: u128, height: u128 },
+    Encoding(png::EncodingError),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            RenderError::TooLarge { width, height } => write!(
+                f,
+                "a {}x{} image is larger than {} pixels",
+                width, height, IMAGE_LIMIT
+            ),
+            RenderError::Encoding(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<png::EncodingError> for RenderError {
+    fn from(e: png::EncodingError) -> RenderError {
+        RenderError::Encoding(e)
+    }
+}
+
+// the puzzle format: the grid from the origin, dots where no line passes
+// and the number of lines elsewhere (# when there are more than 9)
+pub fn to_ascii(map: &VentMap) -> Option<String> {
+    let (min, max) = map.bounds().unwrap_or((Point::new(0, 0), Point::new(0, 0)));
+    let (min_x, min_y) = (min.x.min(0), min.y.min(0));
+    if max.x - min_x >= ASCII_LIMIT || max.y - min_y >= ASCII_LIMIT {
+        return None;
+    }
+
+    let rows = (min_y..=max.y)
+        .map(|y| {
+            (min_x..=max.x)
+                .map(|x| match map.count_at(Point::new(x, y)) {
+                    0 => '.',
+                    n if n > 9 => '#',
+                    n => (b'0' + n as u8) as char,
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<String>>();
+
+    Some(rows.join("\n") + "\n")
+}
+
+// grey levels over the bounding box, the hottest points are white
+fn heat(map: &VentMap) -> Result<(usize, usize, Vec<u8>), RenderError> {
+    let (min, max) = match map.bounds() {
+        Some(bounds) => bounds,
+        None => return Ok((1, 1, vec![0])),
+    };
+    let width = (max.x as i128 - min.x as i128 + 1) as u128;
+    let height = (max.y as i128 - min.y as i128 + 1) as u128;
+    if width * height > IMAGE_LIMIT {
+        return Err(RenderError::TooLarge { width, height });
+    }
+
+    let hottest = map.hottest().0;
+
+    let pixels = (min.y..=max.y)
+        .flat_map(|y| (min.x..=max.x).map(move |x| Point::new(x, y)))
+        .map(|p| (map.count_at(p) * 255 / hottest) as u8)
+        .collect();
+
+    Ok((width as usize, height as usize, pixels))
+}
+
+pub fn to_pgm(map: &VentMap) -> Result<Vec<u8>, RenderError> {
+    let (width, height, pixels) = heat(map)?;
+    let mut output = format!("P5\n{} {}\n255\n", width, height).into_bytes();
+    output.extend(pixels);
+    Ok(output)
+}
+
+pub fn to_png(map: &VentMap) -> Result<Vec<u8>, RenderError> {
+    let (width, height, pixels) = heat(map)?;
+    let too_large = |_| RenderError::TooLarge {
+        width: width as u128,
+        height: height as u128,
+    };
+    let mut output = vec![];
+
+    let mut encoder = png::Encoder::new(
+        &mut output,
+        u32::try_from(width).map_err(too_large)?,
+        u32::try_from(height).map_err(too_large)?,
+    );
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+
+    Ok(output)
+}
+
+fn colour(line: &Line) -> &'static str {
+    if line.is_horizontal() {
+        "steelblue"
+    } else if line.is_vertical() {
+        "seagreen"
+    } else if line.is_diagonal() {
+        "darkorange"
+    } else {
+        "crimson"
+    }
+}
+
+// segments scaled to fit the drawing, coloured by category
+pub fn to_svg<'a, I>(lines: I) -> String
+where
+    I: IntoIterator<Item = &'a Line> + Clone,
+{
+    let origin = Point::new(0, 0);
+    let (min, max) = counter::bounding_box(lines.clone()).unwrap_or((origin, origin));
+
+    let scale = SVG_SIZE / ((max.x - min.x).max(max.y - min.y).max(1) as f64);
+    let x = |p: Point| (p.x - min.x) as f64 * scale;
+    let y = |p: Point| (p.y - min.y) as f64 * scale;
+
+    let segments = lines
+        .into_iter()
+        .map(|l| {
+            format!(
+                "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\"/>",
+                x(l.start),
+                y(l.start),
+                x(l.end),
+                y(l.end),
+                colour(l)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {s} {s}\" width=\"{s}\" height=\"{s}\">\n\
+         <title>Hydrothermal vents</title>\n\
+         <g stroke-width=\"1\" stroke-linecap=\"round\" opacity=\"0.6\">\n{}\n</g>\n\
+         </svg>\n",
+        segments,
+        s = SVG_SIZE,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{example, lines};
+
+    #[test]
+    fn test_ascii() {
+        let expected = "\
+1.1....11.
+.111...2..
+..2.1.111.
+...1.2.2..
+.112313211
+...1.2....
+..1...1...
+.1.....1..
+1.......1.
+222111....
+";
+        assert_eq!(
+            to_ascii(&VentMap::new(&example())),
+            Some(expected.to_string())
+        );
+    }
+
+    #[test]
+    fn test_ascii_too_large() {
+        let lines = lines("0,0 -> 0,500");
+        assert_eq!(to_ascii(&VentMap::new(&lines)), None);
+    }
+
+    #[test]
+    fn test_pgm() {
+        let pgm = to_pgm(&VentMap::new(&example())).unwrap();
+        let header = b"P5\n10 10\n255\n";
+        assert_eq!(&pgm[..header.len()], header);
+        assert_eq!(pgm.len(), header.len() + 100);
+        // 0,0 is covered once, 4,4 three times
+        assert_eq!(pgm[header.len()], 85);
+        assert_eq!(pgm[header.len() + 44], 255);
+        assert_eq!(pgm[header.len() + 1], 0);
+    }
+
+    #[test]
+    fn test_png() {
+        let png = to_png(&VentMap::new(&example())).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let decoder = png::Decoder::new(&png[..]);
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().width, 10);
+        assert_eq!(reader.info().height, 10);
+    }
+
+    #[test]
+    fn test_image_too_large() {
+        let lines = lines("0,0 -> 1,1\n5000000,5000000 -> 5000000,5000000");
+        let map = VentMap::new(&lines);
+        assert!(matches!(
+            to_pgm(&map),
+            Err(RenderError::TooLarge {
+                width: 5000001,
+                height: 5000001
+            })
+        ));
+        assert!(to_png(&map).is_err());
+    }
+
+    #[test]
+    fn test_svg() {
+        let lines = lines("0,0 -> 10,0\n0,0 -> 0,10\n0,0 -> 10,10\n0,0 -> 5,10");
+        let svg = to_svg(&lines);
+        assert!(svg.contains("x2=\"800.00\" y2=\"0.00\" stroke=\"steelblue\""));
+        assert!(svg.contains("stroke=\"seagreen\""));
+        assert!(svg.contains("stroke=\"darkorange\""));
+        assert!(svg.contains("x2=\"400.00\" y2=\"800.00\" stroke=\"crimson\""));
+    }
+}
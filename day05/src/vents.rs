@@ -0,0 +1,142 @@
+use crate::counter::{self, Backend, OverlapCounter};
+use crate::line::{Line, Point};
+use std::collections::BTreeMap;
+
+pub struct VentMap {
+    counter: Box<dyn OverlapCounter>,
+}
+
+impl VentMap {
+    // picks the dense grid when the lines fit in a small box
+    pub fn new<'a, I>(lines: I) -> VentMap
+    where
+        I: IntoIterator<Item = &'a Line> + Clone,
+    {
+        let counter = counter::for_lines(lines.clone());
+        VentMap::with_counter(lines, counter)
+    }
+
+    pub fn with_counter<'a>(
+        lines: impl IntoIterator<Item = &'a Line>,
+        mut counter: Box<dyn OverlapCounter>,
+    ) -> VentMap {
+        lines
+            .into_iter()
+            .flat_map(Line::coverage)
+            .for_each(|p| counter.add(p));
+        VentMap { counter }
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.counter.backend()
+    }
+
+    pub fn count_at(&self, point: Point) -> usize {
+        self.counter.count_at(point)
+    }
+
+    // sorted by row, then column
+    pub fn at_least(&self, k: usize) -> Vec<Point> {
+        let mut points: Vec<Point> = self
+            .counter
+            .counts()
+            .filter(|&(_, v)| v >= k)
+            .map(|(p, _)| p)
+            .collect();
+        points.sort_unstable_by_key(|p| (p.y, p.x));
+        points
+    }
+
+    // the highest overlap count and the points reaching it
+    pub fn hottest(&self) -> (usize, Vec<Point>) {
+        let max = self.counter.counts().map(|(_, v)| v).max().unwrap_or(0);
+        if max == 0 {
+            return (0, vec![]);
+        }
+        (max, self.at_least(max))
+    }
+
+    // corners of the box around the covered points
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let points: Vec<Point> = self.counter.counts().map(|(p, _)| p).collect();
+        let xs = points.iter().map(|p| p.x);
+        let ys = points.iter().map(|p| p.y);
+        Some((
+            Point::new(xs.clone().min()?, ys.clone().min()?),
+            Point::new(xs.max()?, ys.max()?),
+        ))
+    }
+
+    // how many points are covered by each number of lines
+    pub fn histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        self.counter
+            .counts()
+            .for_each(|(_, v)| *histogram.entry(v).or_insert(0) += 1);
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::counter::{Dense, Sparse};
+    use crate::fixtures::example;
+    use crate::line::Filter;
+
+    #[test]
+    fn test_at_least() {
+        let straight = Filter::Horizontal.or(Filter::Vertical);
+        assert_eq!(
+            VentMap::new(straight.select(&example())).at_least(2).len(),
+            5
+        );
+        assert_eq!(VentMap::new(&example()).at_least(2).len(), 12);
+        assert_eq!(VentMap::new(&example()).at_least(1).len(), 39);
+    }
+
+    #[test]
+    fn test_count_at() {
+        let map = VentMap::new(&example());
+        assert_eq!(map.count_at(Point::new(7, 1)), 2);
+        assert_eq!(map.count_at(Point::new(1, 0)), 0);
+        assert_eq!(map.count_at(Point::new(4, 4)), 3);
+    }
+
+    #[test]
+    fn test_hottest() {
+        let map = VentMap::new(&example());
+        assert_eq!(map.hottest(), (3, vec![Point::new(4, 4), Point::new(6, 4)]));
+        assert_eq!(VentMap::new(&[]).hottest(), (0, vec![]));
+    }
+
+    #[test]
+    fn test_backends_give_same_map() {
+        let sparse = VentMap::with_counter(&example(), Box::new(Sparse::default()));
+        let dense = VentMap::with_counter(
+            &example(),
+            Box::new(Dense::new(Point::new(0, 0), Point::new(9, 9))),
+        );
+        assert_eq!(sparse.backend(), Backend::Sparse);
+        assert_eq!(dense.backend(), Backend::Dense);
+        assert_eq!(sparse.histogram(), dense.histogram());
+        assert_eq!(sparse.hottest(), dense.hottest());
+        assert_eq!(sparse.at_least(2), dense.at_least(2));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let map = VentMap::new(&example());
+        assert_eq!(map.bounds(), Some((Point::new(0, 0), Point::new(9, 9))));
+        assert_eq!(VentMap::new(&[]).bounds(), None);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let histogram = VentMap::new(&example()).histogram();
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            vec![(1, 27), (2, 10), (3, 2)]
+        );
+    }
+}
@@ -1,24 +1,28 @@
+// the benchmark compares the step functions, the rest of fishes.rs is only used by main
+#![allow(dead_code)]
+
 use criterion::{criterion_group, criterion_main, Criterion};
 
 mod fishes;
-use fishes::{input_to_array, iterative_step, optimized_step};
+use fishes::{input_to_array, iterative_step, optimized_step, school, PopulationModel};
 
 fn criterion_benchmark(c: &mut Criterion) {
+    let model = PopulationModel::lanternfish();
     c.bench_function("iterative version", |b| {
         b.iter(|| {
-            let mut school = vec![3, 4, 3, 1, 2];
+            let mut school = school(&[3, 4, 3, 1, 2]);
 
             for _ in 0..200 {
-                school = iterative_step(school);
+                school = iterative_step(&model, school);
             }
         })
     });
     c.bench_function("optimized version", |b| {
         b.iter(|| {
-            let mut school = input_to_array(&vec![3, 4, 3, 1, 2]);
+            let mut school = input_to_array(&model, &[3, 4, 3, 1, 2]);
 
             for _ in 0..200 {
-                school = optimized_step(school);
+                school = optimized_step(&model, school);
             }
         })
     });
@@ -1,60 +1,217 @@
-pub fn input_to_array(input: &Vec<u8>) -> [u64; 9] {
-    let mut out = [0; 9];
-    for i in 0..=7 {
-        out[i] = input.iter().filter(|&&v| v == i as u8).count() as u64;
+use std::fmt::{Display, Error, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PopulationModel {
+    // timer of a fish right after spawning
+    pub reset_age: usize,
+    // timer of a newborn fish
+    pub newborn_age: usize,
+    // fishes born at each spawn
+    pub offspring: u64,
+    // a fish dies after spawning this many times, it lives forever when None
+    pub lifespan: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ModelError {
+    // a fish has to spawn at least once before dying
+    ZeroLifespan,
+}
+
+impl Display for ModelError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            ModelError::ZeroLifespan => write!(f, "fishes need to spawn at least once"),
+        }
+    }
+}
+
+impl Default for PopulationModel {
+    fn default() -> PopulationModel {
+        PopulationModel::lanternfish()
+    }
+}
+
+impl PopulationModel {
+    pub fn lanternfish() -> PopulationModel {
+        PopulationModel {
+            reset_age: 6,
+            newborn_age: 8,
+            offspring: 1,
+            lifespan: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ModelError> {
+        match self.lifespan {
+            Some(0) => Err(ModelError::ZeroLifespan),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn timers(&self) -> usize {
+        self.reset_age.max(self.newborn_age) + 1
+    }
+
+    // fishes that spawned a different number of times are tracked separately,
+    // but only when they can die
+    pub fn generations(&self) -> usize {
+        self.lifespan.map_or(1, |n| n as usize)
+    }
+
+    // size of the arrays used by the optimized version: timers for every generation
+    pub fn states(&self) -> usize {
+        self.timers() * self.generations()
+    }
+
+    pub fn index(&self, timer: usize, spawned: usize) -> usize {
+        spawned * self.timers() + timer
+    }
+
+    // where a fish with an expired timer ends up after spawning, if it survives
+    pub fn after_spawn(&self, spawned: usize) -> Option<usize> {
+        match self.lifespan {
+            None => Some(0),
+            Some(_) if spawned + 1 < self.generations() => Some(spawned + 1),
+            Some(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fish {
+    // as wide as the ages of the model
+    pub timer: usize,
+    pub spawned: u32,
+}
+
+#[allow(dead_code)]
+pub fn school(timers: &[u8]) -> Vec<Fish> {
+    timers
+        .iter()
+        .map(|&timer| Fish {
+            timer: timer as usize,
+            spawned: 0,
+        })
+        .collect()
+}
+
+pub fn input_to_array(model: &PopulationModel, input: &[u8]) -> Vec<u64> {
+    let mut out = vec![0; model.states()];
+    for &timer in input {
+        assert!(
+            (timer as usize) < model.timers(),
+            "Timer {} is too big for the model",
+            timer
+        );
+        out[model.index(timer as usize, 0)] += 1;
     }
 
     out
 }
 
 #[allow(dead_code)]
-pub fn iterative_step(v: Vec<u8>) -> Vec<u8> {
+pub fn iterative_step(model: &PopulationModel, v: Vec<Fish>) -> Vec<Fish> {
     let mut v = v;
-    let new_fishes = v.iter().filter(|&&v| v == 0).count();
-    v.iter_mut()
-        .for_each(|v| if *v == 0 { *v = 6 } else { *v = *v - 1 });
+    let spawning = v.iter().filter(|f| f.timer == 0).count();
+    v.retain(|f| f.timer > 0 || model.after_spawn(f.spawned as usize).is_some());
+    v.iter_mut().for_each(|f| {
+        if f.timer == 0 {
+            f.timer = model.reset_age;
+            f.spawned += 1;
+        } else {
+            f.timer -= 1;
+        }
+    });
 
-    let mut new_fishes = vec![8].repeat(new_fishes);
+    let newborns = spawning * model.offspring as usize;
+    let mut new_fishes = vec![
+        Fish {
+            timer: model.newborn_age,
+            spawned: 0
+        };
+        newborns
+    ];
     v.append(&mut new_fishes);
 
     v
 }
 
-pub fn optimized_step(h: [u64; 9]) -> [u64; 9] {
-    let mut out: [u64; 9] = [0; 9];
+pub fn optimized_step(model: &PopulationModel, h: Vec<u64>) -> Vec<u64> {
+    let mut out: Vec<u64> = vec![0; model.states()];
+
+    for spawned in 0..model.generations() {
+        for timer in 1..model.timers() {
+            out[model.index(timer - 1, spawned)] += h[model.index(timer, spawned)];
+        }
 
-    for i in 1..=8 {
-        out[i - 1] = h[i];
+        let spawning = h[model.index(0, spawned)];
+        if let Some(next) = model.after_spawn(spawned) {
+            out[model.index(model.reset_age, next)] += spawning;
+        }
+        out[model.index(model.newborn_age, 0)] += spawning * model.offspring;
     }
-    out[6] = out[6] + h[0];
-    out[8] = h[0];
 
     out
 }
 
+// same as `optimized_step` on u128 counts, none if any of them overflows
+pub fn checked_step(model: &PopulationModel, h: &[u128]) -> Option<Vec<u128>> {
+    let mut out: Vec<u128> = vec![0; model.states()];
+    let mut add = |idx: usize, count: u128| -> Option<()> {
+        out[idx] = out[idx].checked_add(count)?;
+        Some(())
+    };
+
+    for spawned in 0..model.generations() {
+        for timer in 1..model.timers() {
+            add(
+                model.index(timer - 1, spawned),
+                h[model.index(timer, spawned)],
+            )?;
+        }
+
+        let spawning = h[model.index(0, spawned)];
+        if let Some(next) = model.after_spawn(spawned) {
+            add(model.index(model.reset_age, next), spawning)?;
+        }
+        add(
+            model.index(model.newborn_age, 0),
+            spawning.checked_mul(u128::from(model.offspring))?,
+        )?;
+    }
+
+    Some(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn timers(school: &[Fish]) -> Vec<usize> {
+        school.iter().map(|f| f.timer).collect()
+    }
+
     #[test]
     fn test_iterative_step() {
-        let school = vec![3, 4, 3, 1, 2];
-        let result = iterative_step(school);
-        assert_eq!(result, vec![2, 3, 2, 0, 1]);
+        let model = PopulationModel::lanternfish();
+        let result = iterative_step(&model, school(&[3, 4, 3, 1, 2]));
+        assert_eq!(timers(&result), vec![2, 3, 2, 0, 1]);
     }
 
     #[test]
     fn test_iterative_step_with_spawn() {
-        let school = vec![2, 3, 2, 0, 1];
-        let result = iterative_step(school);
-        assert_eq!(result, vec![1, 2, 1, 6, 0, 8])
+        let model = PopulationModel::lanternfish();
+        let result = iterative_step(&model, school(&[2, 3, 2, 0, 1]));
+        assert_eq!(timers(&result), vec![1, 2, 1, 6, 0, 8])
     }
 
     #[test]
     fn test_school_size_after_iterations() {
-        let mut input = vec![3, 4, 3, 1, 2];
+        let model = PopulationModel::lanternfish();
+        let mut input = school(&[3, 4, 3, 1, 2]);
         for _ in 0..18 {
-            input = iterative_step(input);
+            input = iterative_step(&model, input);
         }
 
         assert_eq!(input.len(), 26);
@@ -62,39 +219,138 @@ mod tests {
 
     #[test]
     fn test_input_to_array() {
-        let school = vec![3, 4, 3, 1, 2];
-        let expected: [u64; 9] = [0, 1, 1, 2, 1, 0, 0, 0, 0];
+        let model = PopulationModel::lanternfish();
+        let expected = vec![0, 1, 1, 2, 1, 0, 0, 0, 0];
 
-        assert_eq!(input_to_array(&school), expected);
+        assert_eq!(input_to_array(&model, &[3, 4, 3, 1, 2]), expected);
+        assert_eq!(input_to_array(&model, &[8, 8])[8], 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "too big")]
+    fn test_input_to_array_out_of_range() {
+        input_to_array(&PopulationModel::lanternfish(), &[9]);
     }
 
     #[test]
     fn test_array_step() {
-        let school = input_to_array(&vec![3, 4, 3, 1, 2]);
-        let expected = [1, 1, 2, 1, 0, 0, 0, 0, 0];
-        let result = optimized_step(school);
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[3, 4, 3, 1, 2]);
+        let expected = vec![1, 1, 2, 1, 0, 0, 0, 0, 0];
+        let result = optimized_step(&model, school);
         assert_eq!(result, expected);
     }
 
     #[test]
     fn test_array_step_spawn() {
-        let school = input_to_array(&vec![2, 3, 2, 0, 1]);
-        let expected = [1, 2, 1, 0, 0, 0, 1, 0, 1];
-        let result = optimized_step(school);
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[2, 3, 2, 0, 1]);
+        let expected = vec![1, 2, 1, 0, 0, 0, 1, 0, 1];
+        let result = optimized_step(&model, school);
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_checked_step() {
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[2, 3, 2, 0, 1]);
+        let wide: Vec<u128> = school.iter().map(|&c| u128::from(c)).collect();
+        let expected: Vec<u128> = optimized_step(&model, school)
+            .into_iter()
+            .map(u128::from)
+            .collect();
+        assert_eq!(checked_step(&model, &wide), Some(expected));
+
+        // the spawning fishes reset to 6, where the 7s arrive too
+        let mut huge = vec![0; model.states()];
+        huge[0] = u128::MAX;
+        huge[7] = 1;
+        assert_eq!(checked_step(&model, &huge), None);
+    }
+
+    #[test]
+    fn test_several_offspring() {
+        let model = PopulationModel {
+            offspring: 3,
+            ..PopulationModel::lanternfish()
+        };
+        let result = iterative_step(&model, school(&[0, 1]));
+        assert_eq!(timers(&result), vec![6, 0, 8, 8, 8]);
+
+        let result = optimized_step(&model, input_to_array(&model, &[0, 1]));
+        assert_eq!(result, vec![1, 0, 0, 0, 0, 0, 1, 0, 3]);
+    }
+
+    #[test]
+    fn test_lifespan() {
+        let model = PopulationModel {
+            reset_age: 2,
+            newborn_age: 3,
+            offspring: 1,
+            lifespan: Some(2),
+        };
+        assert_eq!(model.states(), 8);
+
+        // the first fish spawns on days 1 and 4 and dies, its first child spawns on day 5
+        let mut fishes = school(&[0]);
+        let sizes: Vec<usize> = (0..6)
+            .map(|_| {
+                fishes = iterative_step(&model, fishes.clone());
+                fishes.len()
+            })
+            .collect();
+        assert_eq!(sizes, vec![2, 2, 2, 2, 3, 3]);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(PopulationModel::lanternfish().validate(), Ok(()));
+        let model = PopulationModel {
+            lifespan: Some(0),
+            ..PopulationModel::lanternfish()
+        };
+        assert_eq!(model.validate(), Err(ModelError::ZeroLifespan));
+    }
+
+    #[test]
+    fn test_old_ages() {
+        let model = PopulationModel {
+            reset_age: 300,
+            newborn_age: 400,
+            offspring: 1,
+            lifespan: None,
+        };
+        let result = iterative_step(&model, school(&[0]));
+        assert_eq!(timers(&result), vec![300, 400]);
+    }
+
     #[test]
     fn test_equivalence() {
-        let mut input = vec![3, 4, 3, 1, 2];
-        let mut array_input = input_to_array(&input);
+        let models = [
+            PopulationModel::lanternfish(),
+            PopulationModel {
+                offspring: 2,
+                ..PopulationModel::lanternfish()
+            },
+            PopulationModel {
+                reset_age: 4,
+                newborn_age: 2,
+                offspring: 1,
+                lifespan: Some(3),
+            },
+        ];
 
-        for _ in 0..18 {
-            input = iterative_step(input);
-            array_input = optimized_step(array_input);
-        }
+        for model in models {
+            let mut input = school(&[3, 4, 3, 1, 2]);
+            let mut array_input = input_to_array(&model, &[3, 4, 3, 1, 2]);
 
-        let array_sum = array_input.iter().sum::<u64>();
-        assert_eq!(input.len(), array_sum as usize);
+            for _ in 0..18 {
+                input = iterative_step(&model, input);
+                array_input = optimized_step(&model, array_input);
+            }
+
+            let array_sum = array_input.iter().sum::<u64>();
+            assert_eq!(input.len(), array_sum as usize);
+        }
     }
 }
@@ -1,21 +1,147 @@
 mod fishes;
+mod matrix;
+mod series;
 use fishes::*;
+use std::num::NonZeroU64;
 
-fn load_input() -> [u64; 9] {
+fn load_input(model: &PopulationModel) -> Vec<u64> {
     let input = std::fs::read_to_string("input").expect("Unable to read file");
     let input = input.lines().next().expect("Unable to read first line");
     let input = input
-        .split(",")
+        .split(',')
         .map(|x| x.parse::<u8>().expect("Unable to parse"))
         .collect::<Vec<u8>>();
-    input_to_array(&input)
+    input_to_array(model, &input)
+}
+
+enum Report {
+    Total(Solver),
+    Series(Series),
+}
+
+// the reports that simulate every day
+enum Series {
+    Table(String),
+    Growth,
+    Threshold(u64),
+}
+
+enum Solver {
+    Steps,
+    Exact,
+    Big,
+    Modulo(NonZeroU64),
+}
+
+// the model can be changed with `--reset=6 --newborn=8 --offspring=1 --lifespan=3`,
+// and the number of days with `--days=256`. The days are simulated one by one unless
+// `--exact` (u128), `--big` or `--modulo=m` ask for the matrix solver, which is
+// logarithmic in the number of days.
+// Instead of the total, `--series=csv` or `--series=json` print the age histogram of every day,
+// `--growth` estimates the daily growth rate and `--threshold=n` finds the first day with
+// at least n fishes (within the number of days)
+fn settings() -> (PopulationModel, u64, Report) {
+    let mut model = PopulationModel::lanternfish();
+    let mut days = 256;
+    let mut report = Report::Total(Solver::Steps);
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--exact" => report = Report::Total(Solver::Exact),
+            "--big" => report = Report::Total(Solver::Big),
+            "--growth" => report = Report::Series(Series::Growth),
+            _ if arg.starts_with("--series=") => {
+                report = Report::Series(Series::Table(arg["--series=".len()..].to_string()))
+            }
+            _ => {
+                let (name, value) = arg.split_once('=').expect("Expected --name=value");
+                let value = value.parse::<u64>().expect("Invalid value");
+                match name {
+                    "--reset" => model.reset_age = value as usize,
+                    "--newborn" => model.newborn_age = value as usize,
+                    "--offspring" => model.offspring = value,
+                    "--lifespan" => {
+                        model.lifespan = Some(u32::try_from(value).expect("Lifespan too long"))
+                    }
+                    "--days" => days = value,
+                    "--modulo" => {
+                        let modulus = NonZeroU64::new(value).expect("The modulus can't be 0");
+                        report = Report::Total(Solver::Modulo(modulus))
+                    }
+                    "--threshold" => report = Report::Series(Series::Threshold(value)),
+                    _ => panic!("Unknown argument {}", name),
+                }
+            }
+        }
+    }
+
+    if let Err(e) = model.validate() {
+        panic!("Invalid model: {}", e);
+    }
+    (model, days, report)
 }
 
 fn main() {
-    let mut school = load_input();
-    for _ in 0..256 {
-        school = optimized_step(school);
+    let (model, days, report) = settings();
+    let school = load_input(&model);
+
+    let solver = match report {
+        Report::Total(solver) => solver,
+        Report::Series(series) => return print_series(model, school, days as usize, series),
+    };
+
+    match solver {
+        Solver::Steps => {
+            let mut school = school;
+            for _ in 0..days {
+                school = optimized_step(&model, school);
+            }
+            println!("{}", school.iter().sum::<u64>())
+        }
+        Solver::Exact => println!(
+            "{}",
+            matrix::population(&matrix::Exact, &model, &school, days)
+        ),
+        Solver::Big => println!(
+            "{}",
+            matrix::population(&matrix::Big, &model, &school, days)
+        ),
+        Solver::Modulo(m) => println!(
+            "{}",
+            matrix::population(&matrix::Modulo(m), &model, &school, days)
+        ),
     }
+}
+
+fn simulate(simulation: series::Simulation, days: usize) -> Vec<series::Day> {
+    simulation
+        .take(days + 1)
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| panic!("Unable to simulate: {}", e))
+}
 
-    println!("{}", school.iter().sum::<u64>())
+fn print_series(model: PopulationModel, school: Vec<u64>, days: usize, series: Series) {
+    let simulation = series::Simulation::new(model, school);
+    match series {
+        Series::Table(format) => {
+            let days = simulate(simulation, days);
+            match format.as_str() {
+                "csv" => print!("{}", series::to_csv(&days)),
+                "json" => println!("{}", series::to_json(&days)),
+                _ => panic!("Unknown format {}", format),
+            }
+        }
+        Series::Growth => {
+            let days = simulate(simulation, days);
+            match series::growth_rate(&days, (days.len() - 1).min(64)) {
+                Some(rate) => println!("Daily growth: {:.4}", rate),
+                None => println!("Not enough days to estimate the growth"),
+            }
+        }
+        Series::Threshold(threshold) => match simulation.first_day_above(threshold, days) {
+            Ok(Some(day)) => println!("Day {}", day),
+            Ok(None) => println!("Still below {} after {} days", threshold, days),
+            Err(e) => panic!("Unable to simulate: {}", e),
+        },
+    }
 }
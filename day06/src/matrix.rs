@@ -0,0 +1,230 @@
+use crate::fishes::{optimized_step, PopulationModel};
+use num_bigint::BigUint;
+use std::num::NonZeroU64;
+
+// the number type used for the counts, with the operations the matrix product needs
+pub trait Arithmetic {
+    type Value: Clone;
+
+    fn value(&self, v: u64) -> Self::Value;
+    fn add(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+    fn mul(&self, a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+// exact, but it overflows after about a thousand days
+pub struct Exact;
+
+impl Arithmetic for Exact {
+    type Value = u128;
+
+    fn value(&self, v: u64) -> u128 {
+        v as u128
+    }
+
+    fn add(&self, a: &u128, b: &u128) -> u128 {
+        a.checked_add(*b)
+            .expect("Population overflows u128, use big integers or a modulus")
+    }
+
+    fn mul(&self, a: &u128, b: &u128) -> u128 {
+        a.checked_mul(*b)
+            .expect("Population overflows u128, use big integers or a modulus")
+    }
+}
+
+// exact for any number of days, as long as the digits fit in memory
+pub struct Big;
+
+impl Arithmetic for Big {
+    type Value = BigUint;
+
+    fn value(&self, v: u64) -> BigUint {
+        BigUint::from(v)
+    }
+
+    fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a + b
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b
+    }
+}
+
+// the population modulo a number, works for 10^12 days and more
+pub struct Modulo(pub NonZeroU64);
+
+impl Arithmetic for Modulo {
+    type Value = u64;
+
+    fn value(&self, v: u64) -> u64 {
+        v % self.0.get()
+    }
+
+    fn add(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % self.0.get() as u128) as u64
+    }
+
+    fn mul(&self, a: &u64, b: &u64) -> u64 {
+        ((*a as u128 * *b as u128) % self.0.get() as u128) as u64
+    }
+}
+
+type Matrix<T> = Vec<Vec<T>>;
+
+// column j is where the fishes in state j are after a day, so it matches the array stepper
+fn transition<A: Arithmetic>(arithmetic: &A, model: &PopulationModel) -> Matrix<A::Value> {
+    let states = model.states();
+    let columns: Vec<Vec<u64>> = (0..states)
+        .map(|j| {
+            let mut unit = vec![0; states];
+            unit[j] = 1;
+            optimized_step(model, unit)
+        })
+        .collect();
+
+    (0..states)
+        .map(|i| {
+            (0..states)
+                .map(|j| arithmetic.value(columns[j][i]))
+                .collect()
+        })
+        .collect()
+}
+
+fn dot<A: Arithmetic>(arithmetic: &A, row: &[A::Value], column: &[A::Value]) -> A::Value {
+    row.iter()
+        .zip(column)
+        .fold(arithmetic.value(0), |acc, (a, b)| {
+            arithmetic.add(&acc, &arithmetic.mul(a, b))
+        })
+}
+
+fn multiply<A: Arithmetic>(
+    arithmetic: &A,
+    a: &Matrix<A::Value>,
+    b: &Matrix<A::Value>,
+) -> Matrix<A::Value> {
+    let columns: Matrix<A::Value> = (0..b[0].len())
+        .map(|j| b.iter().map(|row| row[j].clone()).collect())
+        .collect();
+
+    a.iter()
+        .map(|row| columns.iter().map(|c| dot(arithmetic, row, c)).collect())
+        .collect()
+}
+
+// exponentiation by squaring: O(states^3 * log(days)) instead of O(states * days)
+pub fn population<A: Arithmetic>(
+    arithmetic: &A,
+    model: &PopulationModel,
+    school: &[u64],
+    days: u64,
+) -> A::Value {
+    let mut power = transition(arithmetic, model);
+    let mut state: Vec<A::Value> = school.iter().map(|&v| arithmetic.value(v)).collect();
+
+    let mut days = days;
+    while days > 0 {
+        if days & 1 == 1 {
+            state = power
+                .iter()
+                .map(|row| dot(arithmetic, row, &state))
+                .collect();
+        }
+        days >>= 1;
+        if days > 0 {
+            power = multiply(arithmetic, &power, &power);
+        }
+    }
+
+    state
+        .iter()
+        .fold(arithmetic.value(0), |acc, v| arithmetic.add(&acc, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fishes::input_to_array;
+
+    fn stepped(model: &PopulationModel, school: &[u64], days: usize) -> u64 {
+        let mut school = school.to_vec();
+        for _ in 0..days {
+            school = optimized_step(model, school);
+        }
+        school.iter().sum()
+    }
+
+    #[test]
+    fn test_example() {
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[3, 4, 3, 1, 2]);
+        assert_eq!(population(&Exact, &model, &school, 18), 26);
+        assert_eq!(population(&Exact, &model, &school, 80), 5934);
+        assert_eq!(population(&Exact, &model, &school, 256), 26984457539);
+        assert_eq!(population(&Exact, &model, &school, 0), 5);
+    }
+
+    #[test]
+    fn test_matches_stepper() {
+        let models = [
+            PopulationModel::lanternfish(),
+            PopulationModel {
+                reset_age: 4,
+                newborn_age: 2,
+                offspring: 3,
+                lifespan: Some(3),
+            },
+        ];
+
+        for model in models {
+            let school = input_to_array(&model, &[3, 4, 3, 1, 2]);
+            for days in [1, 7, 64, 100] {
+                assert_eq!(
+                    population(&Exact, &model, &school, days as u64),
+                    stepped(&model, &school, days) as u128
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[3, 4, 3, 1, 2]);
+
+        let exact = population(&Exact, &model, &school, 700);
+        assert_eq!(population(&Big, &model, &school, 700), BigUint::from(exact));
+
+        let big = population(&Big, &model, &school, 5000);
+        let modulus = NonZeroU64::new(1_000_000_007).unwrap();
+        assert_eq!(
+            BigUint::from(population(&Modulo(modulus), &model, &school, 5000)),
+            big % modulus.get()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows u128")]
+    fn test_exact_overflow() {
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[3, 4, 3, 1, 2]);
+        population(&Exact, &model, &school, 2000);
+    }
+
+    #[test]
+    fn test_huge_number_of_days() {
+        let model = PopulationModel::lanternfish();
+        let school = input_to_array(&model, &[3, 4, 3, 1, 2]);
+        let modulus = NonZeroU64::new(998_244_353).unwrap();
+
+        // a day later, the population is the one of 7 days before plus the one of 9 days before
+        let total = |days| population(&Modulo(modulus), &model, &school, days);
+        let days = 1_000_000_000_000;
+        assert_eq!(
+            total(days),
+            (total(days - 7) + total(days - 9)) % modulus.get()
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use crate::fishes::{checked_step, PopulationModel};
+use serde::Serialize;
+use std::fmt::{Display, Error, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Day {
+    pub day: usize,
+    pub total: u128,
+    // fishes per timer value, whatever the number of times they spawned
+    pub ages: Vec<u128>,
+}
+
+// the first day with more fishes than u128 can count
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Overflow {
+    pub day: usize,
+}
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(
+            f,
+            "the population doesn't fit in 128 bits on day {}",
+            self.day
+        )
+    }
+}
+
+// yields the population of every day, starting from the initial school on day 0,
+// and stops after an overflow
+pub struct Simulation {
+    model: PopulationModel,
+    school: Vec<u128>,
+    day: usize,
+    started: bool,
+    overflowed: bool,
+}
+
+impl Simulation {
+    pub fn new(model: PopulationModel, school: Vec<u64>) -> Simulation {
+        Simulation {
+            model,
+            school: school.into_iter().map(u128::from).collect(),
+            day: 0,
+            started: false,
+            overflowed: false,
+        }
+    }
+
+    // none if the population is still below the threshold after `max_days`
+    pub fn first_day_above(
+        self,
+        threshold: u64,
+        max_days: usize,
+    ) -> Result<Option<usize>, Overflow> {
+        for day in self.take(max_days + 1) {
+            let day = day?;
+            if day.total >= u128::from(threshold) {
+                return Ok(Some(day.day));
+            }
+        }
+        Ok(None)
+    }
+
+    // the school only moves forward when the next day is asked for
+    fn advance(&mut self) -> Result<(), Overflow> {
+        if self.started {
+            self.school =
+                checked_step(&self.model, &self.school).ok_or(Overflow { day: self.day + 1 })?;
+            self.day += 1;
+        }
+        self.started = true;
+        Ok(())
+    }
+
+    fn today(&self) -> Result<Day, Overflow> {
+        let overflow = Overflow { day: self.day };
+        let timers = self.model.timers();
+        let mut ages = vec![0u128; timers];
+        for (state, &count) in self.school.iter().enumerate() {
+            let age = &mut ages[state % timers];
+            *age = age.checked_add(count).ok_or(overflow)?;
+        }
+
+        Ok(Day {
+            day: self.day,
+            total: ages
+                .iter()
+                .try_fold(0u128, |total, &count| total.checked_add(count))
+                .ok_or(overflow)?,
+            ages,
+        })
+    }
+}
+
+impl Iterator for Simulation {
+    type Item = Result<Day, Overflow>;
+
+    fn next(&mut self) -> Option<Result<Day, Overflow>> {
+        if self.overflowed {
+            return None;
+        }
+        let day = self.advance().and_then(|_| self.today());
+        self.overflowed = day.is_err();
+        Some(day)
+    }
+}
+
+pub fn to_csv(days: &[Day]) -> String {
+    let timers = days.first().map_or(0, |d| d.ages.len());
+    let header = (0..timers).fold("day,total".to_string(), |h, t| format!("{},age{}", h, t));
+
+    let rows = days.iter().map(|d| {
+        d.ages
+            .iter()
+            .fold(format!("{},{}", d.day, d.total), |row, count| {
+                format!("{},{}", row, count)
+            })
+    });
+
+    std::iter::once(header)
+        .chain(rows)
+        .map(|row| row + "\n")
+        .collect()
+}
+
+pub fn to_json(days: &[Day]) -> String {
+    serde_json::to_string_pretty(days).expect("Unable to serialize days")
+}
+
+// average daily growth factor over the last `window` days
+pub fn growth_rate(days: &[Day], window: usize) -> Option<f64> {
+    if window == 0 || days.len() <= window {
+        return None;
+    }
+
+    let last = days[days.len() - 1].total as f64;
+    let first = days[days.len() - 1 - window].total as f64;
+    if first == 0.0 {
+        return None;
+    }
+    Some((last / first).powf(1.0 / window as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fishes::input_to_array;
+
+    fn example() -> Simulation {
+        let model = PopulationModel::lanternfish();
+        Simulation::new(model, input_to_array(&model, &[3, 4, 3, 1, 2]))
+    }
+
+    #[test]
+    fn test_simulation() {
+        let days: Vec<Day> = example().take(81).collect::<Result<_, _>>().unwrap();
+        assert_eq!(
+            days[0],
+            Day {
+                day: 0,
+                total: 5,
+                ages: vec![0, 1, 1, 2, 1, 0, 0, 0, 0]
+            }
+        );
+        assert_eq!(days[2].ages, vec![1, 2, 1, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(days[18].total, 26);
+        assert_eq!(days[80].total, 5934);
+    }
+
+    #[test]
+    fn test_ages_merge_generations() {
+        let model = PopulationModel {
+            lifespan: Some(3),
+            ..PopulationModel::lanternfish()
+        };
+        let days: Vec<Day> = Simulation::new(model, input_to_array(&model, &[0]))
+            .take(9)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(days[8].ages, vec![0, 1, 0, 0, 0, 0, 1, 0, 1]);
+        assert_eq!(days[8].total, 3);
+    }
+
+    #[test]
+    fn test_csv() {
+        let csv = to_csv(&example().take(2).collect::<Result<Vec<Day>, _>>().unwrap());
+        assert_eq!(
+            csv,
+            "day,total,age0,age1,age2,age3,age4,age5,age6,age7,age8\n\
+             0,5,0,1,1,2,1,0,0,0,0\n\
+             1,5,1,1,2,1,0,0,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json = to_json(&example().take(1).collect::<Result<Vec<Day>, _>>().unwrap());
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0]["total"], 5);
+        assert_eq!(parsed[0]["ages"][3], 2);
+    }
+
+    #[test]
+    fn test_growth_rate() {
+        let days: Vec<Day> = example().take(257).collect::<Result<_, _>>().unwrap();
+        // the dominant root of x^9 = x^2 + 1
+        let rate = growth_rate(&days, 64).unwrap();
+        assert!((rate - 1.0910).abs() < 0.001, "{}", rate);
+        assert_eq!(growth_rate(&days[..10], 64), None);
+    }
+
+    #[test]
+    fn test_first_day_above() {
+        assert_eq!(example().first_day_above(26, 100), Ok(Some(18)));
+        assert_eq!(example().first_day_above(5, 100), Ok(Some(0)));
+        assert_eq!(example().first_day_above(1_000_000, 10), Ok(None));
+    }
+
+    #[test]
+    fn test_long_runs() {
+        // past day 440 the totals no longer fit in u64
+        let days: Vec<Day> = example().take(501).collect::<Result<_, _>>().unwrap();
+        assert!(days[500].total > u128::from(u64::MAX));
+        assert!(days[500].ages.iter().all(|&count| count > 0));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let model = PopulationModel::lanternfish();
+        let huge = || Simulation::new(model, vec![u64::MAX; model.states()]);
+
+        let mut simulation = huge();
+        let days = simulation.by_ref().take_while(|d| d.is_ok()).count();
+        assert!((400..600).contains(&days), "{}", days);
+        assert_eq!(simulation.next(), None);
+        assert_eq!(huge().nth(days), Some(Err(Overflow { day: days })));
+        // the day that overflows is only computed when it's asked for
+        assert!(huge().take(days).all(|d| d.is_ok()));
+    }
+
+    #[test]
+    fn test_total_above_u64() {
+        let model = PopulationModel::lanternfish();
+        let school = vec![u64::MAX / 2; model.states()];
+        let day = Simulation::new(model, school).next().unwrap().unwrap();
+        assert_eq!(day.total, u128::from(u64::MAX / 2) * 9);
+    }
+}
@@ -1,43 +1,145 @@
+use crate::fuel::{FuelCost, Linear, Triangular};
 use std::ops::RangeInclusive;
 
-pub fn parse(input: &str) -> Vec<i64> {
-    input
-        .split(",")
-        .map(|x| {
-            x.trim()
-                .parse::<i64>()
-                .expect(format!("Unable to parse '{}'", x).as_str())
-        })
-        .collect()
+pub fn fuel_for_position<C: FuelCost + ?Sized>(crabs: &[i64], position: i64, cost: &C) -> i64 {
+    crabs
+        .iter()
+        .fold(0, |acc, &c| acc + cost.fuel((c - position).abs()))
 }
 
-fn linear_fuel_for_position(crabs: &Vec<i64>, position: i64) -> i64 {
-    crabs.iter().fold(0, |acc, &c| (c - position).abs() + acc)
+fn weighted_fuel<C: FuelCost + ?Sized>(
+    crabs: &[i64],
+    weights: &[i64],
+    position: i64,
+    cost: &C,
+) -> i64 {
+    crabs
+        .iter()
+        .zip(weights)
+        .fold(0, |acc, (&c, &w)| acc + w * cost.fuel((c - position).abs()))
 }
 
-fn incremental_fuel_for_position(crabs: &Vec<i64>, position: i64) -> i64 {
-    crabs.iter().fold(0, |acc, &c| {
-        let dist = (c - position).abs();
-        let cons = (dist * (dist + 1)) / 2;
-        acc + cons
-    })
+#[derive(Debug, PartialEq)]
+pub struct Alignment {
+    // every position where the fuel is minimal, in order
+    pub positions: Vec<i64>,
+    pub fuel: i64,
+    // fuel of each crab, in input order, to reach the first optimal position
+    pub breakdown: Vec<i64>,
 }
 
-pub fn find_best_linear_fuel_consumption(crabs: &Vec<i64>) -> i64 {
-    range(crabs)
-        .map(|x| linear_fuel_for_position(crabs, x))
-        .min()
-        .unwrap()
+fn alignment<C: FuelCost + ?Sized>(
+    crabs: &[i64],
+    weights: &[i64],
+    positions: Vec<i64>,
+    cost: &C,
+) -> Alignment {
+    let breakdown: Vec<i64> = crabs
+        .iter()
+        .zip(weights)
+        .map(|(&c, &w)| w * cost.fuel((c - positions[0]).abs()))
+        .collect();
+
+    Alignment {
+        fuel: breakdown.iter().sum(),
+        breakdown,
+        positions,
+    }
 }
 
-pub fn find_best_incremental_fuel_consumption(crabs: &Vec<i64>) -> i64 {
+// tries every position between the first and the last crab, the costs never decrease
+// with the distance so the optimum can't be outside
+fn scan<C: FuelCost + ?Sized>(crabs: &[i64], weights: &[i64], cost: &C) -> Alignment {
+    let curve = weighted_cost_curve(crabs, weights, cost);
+    let fuel = curve.iter().map(|&(_, f)| f).min().unwrap();
+    let positions = curve
+        .into_iter()
+        .filter(|&(_, f)| f == fuel)
+        .map(|(x, _)| x)
+        .collect();
+
+    alignment(crabs, weights, positions, cost)
+}
+
+// first position in lo..=hi where the predicate stops holding, for predicates true then false
+fn partition_point(lo: i64, hi: i64, predicate: impl Fn(i64) -> bool) -> i64 {
+    let (mut lo, mut hi) = (lo, hi + 1);
+    while lo < hi {
+        let middle = lo + (hi - lo) / 2;
+        if predicate(middle) {
+            lo = middle + 1;
+        } else {
+            hi = middle;
+        }
+    }
+    lo
+}
+
+pub fn align<C: FuelCost + ?Sized>(crabs: &[i64], cost: &C) -> Alignment {
+    align_weighted(crabs, &vec![1; crabs.len()], cost)
+}
+
+// with a convex cost (and positive weights) the total only decreases until the optimum,
+// stays flat on the ties and then increases, so binary searches find both ends;
+// otherwise every position is tried
+pub fn align_weighted<C: FuelCost + ?Sized>(crabs: &[i64], weights: &[i64], cost: &C) -> Alignment {
+    if !cost.is_convex() {
+        return scan(crabs, weights, cost);
+    }
+
+    let range = range(crabs);
+    let (start, end) = (*range.start(), *range.end());
+    let total = |x| weighted_fuel(crabs, weights, x, cost);
+
+    let first = partition_point(start, end - 1, |x| total(x) > total(x + 1));
+    let fuel = total(first);
+    let last = partition_point(first, end, |x| total(x) == fuel) - 1;
+
+    alignment(crabs, weights, (first..=last).collect(), cost)
+}
+
+pub fn weighted_cost_curve<C: FuelCost + ?Sized>(
+    crabs: &[i64],
+    weights: &[i64],
+    cost: &C,
+) -> Vec<(i64, i64)> {
     range(crabs)
-        .map(|x| incremental_fuel_for_position(crabs, x))
+        .map(|x| (x, weighted_fuel(crabs, weights, x, cost)))
+        .collect()
+}
+
+// the total fuel for every position between the first and the last crab
+pub fn cost_curve<C: FuelCost + ?Sized>(crabs: &[i64], cost: &C) -> Vec<(i64, i64)> {
+    weighted_cost_curve(crabs, &vec![1; crabs.len()], cost)
+}
+
+pub fn curve_to_csv(curve: &[(i64, i64)]) -> String {
+    curve
+        .iter()
+        .fold("position,fuel\n".to_string(), |csv, (x, f)| {
+            format!("{}{},{}\n", csv, x, f)
+        })
+}
+
+// the sum of distances is minimal at any median
+pub fn median_linear_fuel_consumption(crabs: &[i64]) -> i64 {
+    let mut crabs = crabs.to_vec();
+    let middle = crabs.len() / 2;
+    let (_, &mut median, _) = crabs.select_nth_unstable(middle);
+    fuel_for_position(&crabs, median, &Linear)
+}
+
+// the derivative of the triangular cost is (x - c) + sign(x - c) / 2 per crab,
+// so the real minimum is within 1/2 of the mean and the best integer is next to it
+pub fn mean_incremental_fuel_consumption(crabs: &[i64]) -> i64 {
+    let mean = crabs.iter().sum::<i64>().div_euclid(crabs.len() as i64);
+    (mean - 1..=mean + 2)
+        .map(|x| fuel_for_position(crabs, x, &Triangular))
         .min()
         .unwrap()
 }
 
-fn range(crabs: &Vec<i64>) -> RangeInclusive<i64> {
+fn range(crabs: &[i64]) -> RangeInclusive<i64> {
     let (min, max) = crabs.iter().fold((i64::MAX, i64::MIN), |(min, max), &c| {
         (c.min(min), c.max(max))
     });
@@ -48,18 +150,32 @@ fn range(crabs: &Vec<i64>) -> RangeInclusive<i64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fuel::{Capped, Convex, Quadratic};
+
+    // deterministic pseudo-random crabs
+    fn random_crabs(seed: u64, count: usize, max: i64) -> Vec<i64> {
+        let mut state = seed;
+        (0..count)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((state >> 33) % (max as u64 + 1)) as i64
+            })
+            .collect()
+    }
 
     #[test]
     fn test_linear_fuel_for_position() {
         let crabs = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        let fuel = linear_fuel_for_position(&crabs, 2);
+        let fuel = fuel_for_position(&crabs, 2, &Linear);
         assert_eq!(fuel, 37)
     }
 
     #[test]
     fn test_incremental_fuel_for_position() {
         let crabs = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        let fuel = incremental_fuel_for_position(&crabs, 2);
+        let fuel = fuel_for_position(&crabs, 2, &Triangular);
         assert_eq!(fuel, 206)
     }
 
@@ -73,7 +189,124 @@ mod tests {
     #[test]
     fn test_find_best_fuel_consumption() {
         let crabs = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
-        let best_fuel = find_best_linear_fuel_consumption(&crabs);
-        assert_eq!(best_fuel, 37);
+        let best = align(&crabs, &Linear);
+        assert_eq!(best.fuel, 37);
+        assert_eq!(best.positions, vec![2]);
+    }
+
+    #[test]
+    fn test_closed_forms() {
+        let crabs = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        assert_eq!(median_linear_fuel_consumption(&crabs), 37);
+        assert_eq!(mean_incremental_fuel_consumption(&crabs), 168);
+    }
+
+    #[test]
+    fn test_closed_forms_match_brute_force() {
+        for seed in 0..200 {
+            let count = 1 + seed as usize % 17;
+            let max = [3, 50, 1000][seed as usize % 3];
+            let crabs = random_crabs(seed, count, max);
+
+            assert_eq!(
+                median_linear_fuel_consumption(&crabs),
+                scan(&crabs, &vec![1; crabs.len()], &Linear).fuel,
+                "{:?}",
+                crabs
+            );
+            assert_eq!(
+                mean_incremental_fuel_consumption(&crabs),
+                scan(&crabs, &vec![1; crabs.len()], &Triangular).fuel,
+                "{:?}",
+                crabs
+            );
+        }
+    }
+
+    #[test]
+    fn test_convex_alignment_matches_scan() {
+        let costs: Vec<Box<dyn FuelCost>> = vec![
+            Box::new(Linear),
+            Box::new(Triangular),
+            Box::new(Quadratic),
+            Box::new(Convex(|d: i64| d * d * d)),
+        ];
+
+        for seed in 0..100 {
+            let crabs = random_crabs(seed, 1 + seed as usize % 13, 300);
+            let weights: Vec<i64> = random_crabs(seed + 1000, crabs.len(), 9)
+                .iter()
+                .map(|w| w + 1)
+                .collect();
+            for cost in &costs {
+                assert_eq!(
+                    align(&crabs, cost.as_ref()),
+                    scan(&crabs, &vec![1; crabs.len()], cost.as_ref()),
+                    "{:?}",
+                    crabs
+                );
+                assert_eq!(
+                    align_weighted(&crabs, &weights, cost.as_ref()),
+                    scan(&crabs, &weights, cost.as_ref()),
+                    "{:?}",
+                    crabs
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_non_convex_costs() {
+        // capped costs reward going to the crowd, even if far away
+        let crabs = vec![0, 1, 2, 100, 100, 100, 100];
+        assert_eq!(align(&crabs, &Capped(Box::new(Linear), 10)).fuel, 30);
+        let root = |d: i64| (d as f64).sqrt() as i64;
+        assert_eq!(align(&crabs, &root).fuel, 28);
+        assert_eq!(align(&crabs, &root).positions, vec![100]);
+    }
+
+    #[test]
+    fn test_alignment() {
+        let crabs = vec![16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        let best = align(&crabs, &Triangular);
+        assert_eq!(best.positions, vec![5]);
+        assert_eq!(best.fuel, 168);
+        assert_eq!(best.breakdown, vec![66, 10, 6, 15, 1, 6, 3, 10, 6, 45]);
+        assert_eq!(best.breakdown.iter().sum::<i64>(), best.fuel);
+    }
+
+    #[test]
+    fn test_alignment_ties() {
+        // any position between the two middle crabs is a median
+        let best = align(&[1, 4, 8, 10], &Linear);
+        assert_eq!(best.positions, vec![4, 5, 6, 7, 8]);
+        assert_eq!(best.fuel, 13);
+
+        let best = align(&[0, 1, 2, 100, 101, 102], &Capped(Box::new(Linear), 10));
+        assert_eq!(best.positions, vec![1, 101]);
+        assert_eq!(best.fuel, 32);
+    }
+
+    #[test]
+    fn test_single_crab() {
+        let best = align(&[7], &Quadratic);
+        assert_eq!(best.positions, vec![7]);
+        assert_eq!(best.breakdown, vec![0]);
+    }
+
+    #[test]
+    fn test_cost_curve() {
+        let curve = cost_curve(&[0, 2], &Triangular);
+        assert_eq!(curve, vec![(0, 3), (1, 2), (2, 3)]);
+        assert_eq!(curve_to_csv(&curve), "position,fuel\n0,3\n1,2\n2,3\n");
+    }
+
+    #[test]
+    fn test_weighted_alignment() {
+        // a crab ten times as hungry pulls the median to itself
+        let best = align_weighted(&[0, 5, 10], &[1, 1, 10], &Linear);
+        assert_eq!(best.positions, vec![10]);
+        assert_eq!(best.fuel, 15);
+        assert_eq!(best.breakdown, vec![10, 5, 0]);
     }
 }
@@ -0,0 +1,345 @@
+use crate::crabs::{align_weighted, weighted_cost_curve, Alignment};
+use crate::fuel::FuelCost;
+use std::fmt::{Display, Error, Formatter};
+
+pub const MAX_DIMENSIONS: usize = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crab {
+    pub position: Vec<i64>,
+    // fuel spent per unit of cost, the crab's efficiency
+    pub weight: i64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CrabError {
+    Empty,
+    // lines are 1-based
+    InvalidNumber {
+        line: usize,
+        found: String,
+    },
+    InvalidWeight {
+        line: usize,
+        found: String,
+    },
+    TooManyDimensions {
+        line: usize,
+        found: usize,
+    },
+    MixedDimensions {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Display for CrabError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            CrabError::Empty => write!(f, "no crabs in the input"),
+            CrabError::InvalidNumber { line, found } => {
+                write!(f, "invalid coordinate '{}' at line {}", found, line)
+            }
+            CrabError::InvalidWeight { line, found } => write!(
+                f,
+                "invalid weight '{}' at line {}, it must be a positive number",
+                found, line
+            ),
+            CrabError::TooManyDimensions { line, found } => write!(
+                f,
+                "crab at line {} has {} coordinates, at most {} are supported",
+                line, found, MAX_DIMENSIONS
+            ),
+            CrabError::MixedDimensions {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "crab at line {} has {} coordinates, the first one has {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+fn number(line: usize, text: &str) -> Result<i64, CrabError> {
+    text.trim().parse().map_err(|_| CrabError::InvalidNumber {
+        line,
+        found: text.trim().to_string(),
+    })
+}
+
+// `x [y [z]] [*weight]`
+fn parse_crab(line: usize, text: &str) -> Result<Crab, CrabError> {
+    let (coordinates, weight) = match text.split_once('*') {
+        Some((coordinates, weight)) => {
+            let weight = weight
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&w| w > 0)
+                .ok_or_else(|| CrabError::InvalidWeight {
+                    line,
+                    found: weight.trim().to_string(),
+                })?;
+            (coordinates, weight)
+        }
+        None => (text, 1),
+    };
+
+    let position = coordinates
+        .split_whitespace()
+        .map(|c| number(line, c))
+        .collect::<Result<Vec<i64>, CrabError>>()?;
+    if position.is_empty() {
+        return Err(CrabError::InvalidNumber {
+            line,
+            found: coordinates.trim().to_string(),
+        });
+    }
+    if position.len() > MAX_DIMENSIONS {
+        return Err(CrabError::TooManyDimensions {
+            line,
+            found: position.len(),
+        });
+    }
+
+    Ok(Crab { position, weight })
+}
+
+// the puzzle input is a single line of 1D positions separated by commas, anything else
+// has a crab per line with its coordinates separated by spaces (blank lines are skipped)
+pub fn parse_crabs(input: &str) -> Result<Vec<Crab>, CrabError> {
+    let lines: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| (idx + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty())
+        .collect();
+
+    let crabs = match lines[..] {
+        [] => return Err(CrabError::Empty),
+        [(line, text)] if text.contains(',') => text
+            .split(',')
+            .map(|c| {
+                number(line, c).map(|x| Crab {
+                    position: vec![x],
+                    weight: 1,
+                })
+            })
+            .collect::<Result<Vec<Crab>, CrabError>>()?,
+        _ => lines
+            .iter()
+            .map(|&(line, text)| parse_crab(line, text))
+            .collect::<Result<Vec<Crab>, CrabError>>()?,
+    };
+
+    let expected = crabs[0].position.len();
+    if let Some((idx, crab)) = crabs
+        .iter()
+        .enumerate()
+        .find(|(_, c)| c.position.len() != expected)
+    {
+        return Err(CrabError::MixedDimensions {
+            line: lines[idx].0,
+            expected,
+            found: crab.position.len(),
+        });
+    }
+
+    Ok(crabs)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Meeting {
+    // the first optimal point; any combination of the optimal positions of each axis is optimal
+    pub point: Vec<i64>,
+    pub axes: Vec<Alignment>,
+    pub fuel: i64,
+    // fuel of each crab, in input order, to reach the point
+    pub breakdown: Vec<i64>,
+}
+
+fn axis(crabs: &[Crab], axis: usize) -> Vec<i64> {
+    crabs.iter().map(|c| c.position[axis]).collect()
+}
+
+// the cost is paid separately on each axis (for a linear cost, that's the manhattan distance),
+// so each axis is a weighted 1D alignment and the optimal point is made of their optima.
+// Costs that depend on the whole move, like a capped one, can't be split that way
+pub fn meet<C: FuelCost + ?Sized>(crabs: &[Crab], cost: &C) -> Meeting {
+    let weights: Vec<i64> = crabs.iter().map(|c| c.weight).collect();
+    let dimensions = crabs[0].position.len();
+
+    let axes: Vec<Alignment> = (0..dimensions)
+        .map(|a| align_weighted(&axis(crabs, a), &weights, cost))
+        .collect();
+
+    let breakdown = (0..crabs.len())
+        .map(|idx| axes.iter().map(|a| a.breakdown[idx]).sum())
+        .collect();
+
+    Meeting {
+        point: axes.iter().map(|a| a.positions[0]).collect(),
+        fuel: axes.iter().map(|a| a.fuel).sum(),
+        axes,
+        breakdown,
+    }
+}
+
+// the total fuel of every position of each axis, the fuel of a point is the sum over its axes
+pub fn axis_curves<C: FuelCost + ?Sized>(crabs: &[Crab], cost: &C) -> Vec<Vec<(i64, i64)>> {
+    let weights: Vec<i64> = crabs.iter().map(|c| c.weight).collect();
+    (0..crabs[0].position.len())
+        .map(|a| weighted_cost_curve(&axis(crabs, a), &weights, cost))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuel::{Linear, Triangular};
+
+    fn crab(position: Vec<i64>, weight: i64) -> Crab {
+        Crab { position, weight }
+    }
+
+    #[test]
+    fn test_parse_puzzle_format() {
+        let crabs = parse_crabs("16,1,2\n").unwrap();
+        assert_eq!(
+            crabs,
+            vec![crab(vec![16], 1), crab(vec![1], 1), crab(vec![2], 1)]
+        );
+    }
+
+    #[test]
+    fn test_parse_extended_format() {
+        let crabs = parse_crabs("1 2 3\n\n-4  5 6 * 2\n").unwrap();
+        assert_eq!(crabs, vec![crab(vec![1, 2, 3], 1), crab(vec![-4, 5, 6], 2)]);
+
+        let crabs = parse_crabs("7 8 *3").unwrap();
+        assert_eq!(crabs, vec![crab(vec![7, 8], 3)]);
+    }
+
+    #[test]
+    fn test_parse_single_crab() {
+        // commas are only used by the puzzle format, spaces only by the extended one
+        assert_eq!(parse_crabs("3 4\n").unwrap(), vec![crab(vec![3, 4], 1)]);
+        assert_eq!(parse_crabs("1 2 3").unwrap(), vec![crab(vec![1, 2, 3], 1)]);
+        assert_eq!(
+            parse_crabs("3,4\n").unwrap(),
+            vec![crab(vec![3], 1), crab(vec![4], 1)]
+        );
+        assert_eq!(parse_crabs("5").unwrap(), vec![crab(vec![5], 1)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_crabs(" \n"), Err(CrabError::Empty));
+        assert_eq!(
+            parse_crabs("1 2\n3 x\n"),
+            Err(CrabError::InvalidNumber {
+                line: 2,
+                found: "x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_crabs("1 2 *0\n3 4\n"),
+            Err(CrabError::InvalidWeight {
+                line: 1,
+                found: "0".to_string()
+            })
+        );
+        assert_eq!(
+            parse_crabs("1 2 3 4\n1 2 3 4\n"),
+            Err(CrabError::TooManyDimensions { line: 1, found: 4 })
+        );
+        assert_eq!(
+            parse_crabs("1,2\n3,4\n"),
+            Err(CrabError::InvalidNumber {
+                line: 1,
+                found: "1,2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_crabs("*2\n3\n"),
+            Err(CrabError::InvalidNumber {
+                line: 1,
+                found: "".to_string()
+            })
+        );
+        assert_eq!(
+            parse_crabs("1 2\n\n3\n"),
+            Err(CrabError::MixedDimensions {
+                line: 3,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_meet_1d_matches_alignment() {
+        let crabs = parse_crabs("16,1,2,0,4,2,7,1,2,14").unwrap();
+        let meeting = meet(&crabs, &Triangular);
+        assert_eq!(meeting.point, vec![5]);
+        assert_eq!(meeting.fuel, 168);
+    }
+
+    #[test]
+    fn test_meet_2d() {
+        let crabs = parse_crabs("0 0\n4 0\n0 4\n4 4 *3\n").unwrap();
+        let tied = meet(&parse_crabs("0 0\n4 4\n").unwrap(), &Linear);
+        assert_eq!(tied.axes[1].positions, vec![0, 1, 2, 3, 4]);
+
+        let meeting = meet(&crabs, &Linear);
+        // the heavy crab pulls the median of both axes to itself
+        assert_eq!(meeting.axes[0].positions, vec![4]);
+        assert_eq!(meeting.point, vec![4, 4]);
+        assert_eq!(meeting.fuel, 16);
+        assert_eq!(meeting.breakdown, vec![8, 4, 4, 0]);
+    }
+
+    #[test]
+    fn test_meet_3d_matches_brute_force() {
+        let crabs = parse_crabs("1 5 2 *2\n3 0 7\n8 2 2 *4\n0 9 1\n").unwrap();
+        let meeting = meet(&crabs, &Triangular);
+
+        let fuel = |p: [i64; 3]| -> i64 {
+            crabs
+                .iter()
+                .map(|c| {
+                    (0..3)
+                        .map(|a| c.weight * Triangular.fuel((c.position[a] - p[a]).abs()))
+                        .sum::<i64>()
+                })
+                .sum()
+        };
+        let best = (0..10)
+            .flat_map(|x| (0..10).flat_map(move |y| (0..10).map(move |z| [x, y, z])))
+            .map(fuel)
+            .min()
+            .unwrap();
+        assert_eq!(meeting.fuel, best);
+        assert_eq!(
+            fuel([meeting.point[0], meeting.point[1], meeting.point[2]]),
+            best
+        );
+    }
+
+    #[test]
+    fn test_axis_curves() {
+        let crabs = parse_crabs("0 *3\n4\n").unwrap();
+        assert_eq!(
+            axis_curves(&crabs, &Linear),
+            vec![vec![(0, 4), (1, 6), (2, 8), (3, 10), (4, 12)]]
+        );
+
+        let curves = axis_curves(&parse_crabs("0 0\n2 4\n").unwrap(), &Linear);
+        assert_eq!(curves.len(), 2);
+        assert_eq!(curves[1].len(), 5);
+    }
+}
@@ -0,0 +1,105 @@
+// fuel spent by a crab to move by a distance, it must never decrease with the distance:
+// moving past the last crab then costs more to everyone, so only positions between
+// the first and the last crab are tried
+pub trait FuelCost {
+    fn fuel(&self, distance: i64) -> i64;
+
+    // true when the cost is convex and never decreases with the distance,
+    // so the total fuel is convex in the position and can be searched
+    fn is_convex(&self) -> bool {
+        false
+    }
+}
+
+pub struct Linear;
+
+impl FuelCost for Linear {
+    fn fuel(&self, distance: i64) -> i64 {
+        distance
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+// 1 + 2 + ... + distance
+pub struct Triangular;
+
+impl FuelCost for Triangular {
+    fn fuel(&self, distance: i64) -> i64 {
+        distance * (distance + 1) / 2
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+pub struct Quadratic;
+
+impl FuelCost for Quadratic {
+    fn fuel(&self, distance: i64) -> i64 {
+        distance * distance
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+// a cost that never goes above the cap, which makes it concave past the cap
+pub struct Capped(pub Box<dyn FuelCost>, pub i64);
+
+impl FuelCost for Capped {
+    fn fuel(&self, distance: i64) -> i64 {
+        self.0.fuel(distance).min(self.1)
+    }
+}
+
+// any non-decreasing closure is a cost, assumed not to be convex
+impl<F: Fn(i64) -> i64> FuelCost for F {
+    fn fuel(&self, distance: i64) -> i64 {
+        self(distance)
+    }
+}
+
+// a closure declared as convex
+pub struct Convex<F: Fn(i64) -> i64>(pub F);
+
+impl<F: Fn(i64) -> i64> FuelCost for Convex<F> {
+    fn fuel(&self, distance: i64) -> i64 {
+        (self.0)(distance)
+    }
+
+    fn is_convex(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_costs() {
+        assert_eq!(Linear.fuel(5), 5);
+        assert_eq!(Triangular.fuel(5), 15);
+        assert_eq!(Quadratic.fuel(5), 25);
+        assert_eq!(Capped(Box::new(Triangular), 10).fuel(5), 10);
+        assert_eq!(Capped(Box::new(Triangular), 10).fuel(3), 6);
+        assert_eq!((|d: i64| 3 * d).fuel(5), 15);
+        assert_eq!(Convex(|d: i64| d * d * d).fuel(2), 8);
+    }
+
+    #[test]
+    fn test_convexity() {
+        assert!(Linear.is_convex());
+        assert!(!Capped(Box::new(Linear), 10).is_convex());
+        assert!(!(|d: i64| d).is_convex());
+        assert!(Convex(|d: i64| d).is_convex());
+
+        let boxed: Box<dyn FuelCost> = Box::new(Quadratic);
+        assert!(boxed.is_convex());
+    }
+}
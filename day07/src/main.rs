@@ -1,20 +1,147 @@
 mod crabs;
-use crabs::{find_best_incremental_fuel_consumption, find_best_linear_fuel_consumption, parse};
+mod fleet;
+mod fuel;
+use crabs::{
+    align, cost_curve, curve_to_csv, mean_incremental_fuel_consumption,
+    median_linear_fuel_consumption,
+};
+use fleet::{axis_curves, meet, parse_crabs, Crab};
+use fuel::{Capped, Convex, FuelCost, Linear, Quadratic, Triangular};
 
-fn load_file() -> Vec<i64> {
-    let input = std::fs::read_to_string("input").expect("File not found");
-    parse(&input)
+// `--input=file` reads another file, which can also have weighted 2D or 3D crabs,
+// one per line with the coordinates separated by spaces (e.g. `3 4 *2`)
+fn load_file() -> Vec<Crab> {
+    let path = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.strip_prefix("--input=").map(str::to_string))
+        .unwrap_or_else(|| "input".to_string());
+    let input = std::fs::read_to_string(path).expect("File not found");
+    parse_crabs(&input).unwrap_or_else(|e| panic!("Invalid input: {}", e))
+}
+
+// `--cost=linear`, `triangular`, `quadratic` or `cubic` also prints the best fuel
+// for that cost, and `--cap=n` caps the fuel of every crab to n (it needs a `--cost`,
+// and 1D crabs since the fuel is computed separately on each axis)
+fn custom_cost(dimensions: usize) -> Option<Box<dyn FuelCost>> {
+    let mut cost: Option<Box<dyn FuelCost>> = None;
+    let mut cap = None;
+
+    for arg in std::env::args().skip(1) {
+        if let Some(name) = arg.strip_prefix("--cost=") {
+            cost = Some(match name {
+                "linear" => Box::new(Linear),
+                "triangular" => Box::new(Triangular),
+                "quadratic" => Box::new(Quadratic),
+                "cubic" => Box::new(Convex(|d: i64| d * d * d)),
+                _ => panic!("Unknown cost {}", name),
+            });
+        } else if let Some(n) = arg.strip_prefix("--cap=") {
+            cap = Some(n.parse::<i64>().expect("Invalid cap"));
+        }
+    }
+
+    match (cost, cap) {
+        (None, Some(_)) => panic!("--cap needs a --cost to cap"),
+        (Some(_), Some(_)) if dimensions > 1 => {
+            panic!("--cap only works with 1D crabs, a capped cost can't be split by axis")
+        }
+        (Some(cost), Some(cap)) => Some(Box::new(Capped(cost, cap))),
+        (cost, None) => cost,
+    }
+}
+
+// `--positions` prints where the crabs meet, `--breakdown` the fuel of every crab
+// and `--curve` writes the total fuel of every position to `<cost>.csv`
+fn flag(name: &str) -> bool {
+    std::env::args().skip(1).any(|a| a == name)
+}
+
+fn report(input: &[i64], name: &str, cost: &dyn FuelCost) {
+    if flag("--positions") || flag("--breakdown") {
+        let best = align(input, cost);
+        println!("Best {} positions: {:?}", name, best.positions);
+        if flag("--breakdown") {
+            println!(
+                "Fuel per crab at {}: {:?}",
+                best.positions[0], best.breakdown
+            );
+        }
+    }
+
+    if flag("--curve") {
+        let csv = curve_to_csv(&cost_curve(input, cost));
+        std::fs::write(format!("{}.csv", name), csv).expect("Unable to write CSV");
+    }
 }
 
 fn main() {
-    let input = load_file();
+    let crabs = load_file();
+    let custom = custom_cost(crabs[0].position.len());
+    if crabs.iter().any(|c| c.position.len() > 1 || c.weight > 1) {
+        return main_fleet(&crabs, custom);
+    }
+
+    let input: Vec<i64> = crabs.iter().map(|c| c.position[0]).collect();
     println!(
         "Best linear fuel consumption: {}",
-        find_best_linear_fuel_consumption(&input)
+        median_linear_fuel_consumption(&input)
     );
+    report(&input, "linear", &Linear);
 
     println!(
         "Best incremental fuel consumption: {}",
-        find_best_incremental_fuel_consumption(&input)
+        mean_incremental_fuel_consumption(&input)
     );
+    report(&input, "incremental", &Triangular);
+
+    if let Some(cost) = custom {
+        println!(
+            "Best custom fuel consumption: {}",
+            align(&input, cost.as_ref()).fuel
+        );
+        report(&input, "custom", cost.as_ref());
+    }
+}
+
+// weighted or multi-dimensional crabs get the meeting point and its fuel, the flags of
+// `report` print the positions of each axis and write a curve per axis (`<cost>_<axis>.csv`
+// when there's more than one)
+fn main_fleet(crabs: &[Crab], custom: Option<Box<dyn FuelCost>>) {
+    let mut costs: Vec<(&str, Box<dyn FuelCost>)> = vec![
+        ("linear", Box::new(Linear)),
+        ("incremental", Box::new(Triangular)),
+    ];
+    if let Some(cost) = custom {
+        costs.push(("custom", cost));
+    }
+
+    for (name, cost) in costs {
+        let meeting = meet(crabs, cost.as_ref());
+        println!(
+            "Best {} meeting point: {:?} with fuel {}",
+            name, meeting.point, meeting.fuel
+        );
+
+        if flag("--positions") || flag("--breakdown") {
+            let positions: Vec<&Vec<i64>> = meeting.axes.iter().map(|a| &a.positions).collect();
+            println!("Best {} positions per axis: {:?}", name, positions);
+            if flag("--breakdown") {
+                println!(
+                    "Fuel per crab at {:?}: {:?}",
+                    meeting.point, meeting.breakdown
+                );
+            }
+        }
+
+        if flag("--curve") {
+            let curves = axis_curves(crabs, cost.as_ref());
+            for (axis, curve) in curves.iter().enumerate() {
+                let file = match curves.len() {
+                    1 => format!("{}.csv", name),
+                    _ => format!("{}_{}.csv", name, ["x", "y", "z"][axis]),
+                };
+                std::fs::write(file, curve_to_csv(curve)).expect("Unable to write CSV");
+            }
+        }
+    }
 }
@@ -0,0 +1,84 @@
+// parsing and decoding are measured, the rest of the binary's code isn't called
+#![allow(dead_code)]
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+mod decoder;
+// its tests are left out of the benchmark, but not the imports of its test module
+#[allow(unused_imports)]
+mod input;
+mod signal;
+use input::Input;
+
+const DIGITS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+// deterministic pseudo-random entries: the wires are shuffled for every display,
+// and so are the ten patterns and the letters of each one
+fn generate_entries(count: usize) -> Vec<String> {
+    let mut state: u64 = 0x2021_0008;
+    let mut next = |bound: usize| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((state >> 33) % bound as u64) as usize
+    };
+    let mut shuffle = |items: &mut [u8]| {
+        for i in (1..items.len()).rev() {
+            items.swap(i, next(i + 1));
+        }
+    };
+
+    (0..count)
+        .map(|_| {
+            let mut wires = *b"abcdefg";
+            shuffle(&mut wires);
+            let mut order = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+            shuffle(&mut order);
+
+            let mut wired = |digit: u8| {
+                let mut segments: Vec<u8> = DIGITS[digit as usize]
+                    .bytes()
+                    .map(|s| wires[(s - b'a') as usize])
+                    .collect();
+                shuffle(&mut segments);
+                String::from_utf8(segments).unwrap()
+            };
+            let patterns: Vec<String> = order.iter().map(|&d| wired(d)).collect();
+            let digits: Vec<String> = order[..4].iter().map(|&d| wired(d)).collect();
+
+            format!("{} | {}", patterns.join(" "), digits.join(" "))
+        })
+        .collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("entries");
+    group.sample_size(10);
+
+    for count in [1_000, 1_000_000] {
+        let lines = generate_entries(count);
+        let entries: Vec<Input> = lines.iter().map(|l| l.parse().unwrap()).collect();
+
+        group.bench_with_input(BenchmarkId::new("decode", count), &entries, |b, e| {
+            b.iter(|| e.iter().map(decoder::digits).sum::<Result<i64, _>>())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("parse and decode", count),
+            &lines,
+            |b, l| {
+                b.iter(|| {
+                    l.iter()
+                        .map(|line| decoder::digits(&line.parse().unwrap()))
+                        .sum::<Result<i64, _>>()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -1,100 +1,106 @@
-use crate::input::Input;
-use crate::signal;
+use crate::input::{Input, InputError};
+use crate::signal::Pattern;
+use std::ops::Range;
 
-pub fn digits(input: &Input) -> i64 {
-    let assoc = associate_numbers(&input.patterns);
-    input
-        .digits
+pub fn digits(input: &Input) -> Result<i64, InputError> {
+    let assoc = associate_numbers(&input.patterns).ok_or(InputError::NoWiring)?;
+
+    // every pattern is below 128, so a table replaces the search among the ten digits
+    let mut table = [None; 128];
+    assoc
         .iter()
-        .map(|digit| {
-            assoc
-                .iter()
-                .position(|e| signal::matches(e, digit))
-                .unwrap()
-                .to_string()
-        })
-        .collect::<String>()
-        .parse::<i64>()
-        .unwrap()
+        .enumerate()
+        .for_each(|(digit, pattern)| table[pattern.0 as usize] = Some(digit as u8));
+
+    input.digits.iter().try_fold(0, |acc, &digit| {
+        let value = table[digit.0 as usize].ok_or(InputError::UnknownDigit(digit))?;
+        Ok(acc * 10 + value as i64)
+    })
 }
 
-fn associate_numbers(input: &Vec<String>) -> [String; 10] {
-    let mut out: [String; 10] = Default::default();
+// where each length starts once the patterns are sorted by length: 1, 7, 4, the three
+// 5-length ones, the three 6-length ones and 8. Lengths no digit has are out of bounds
+const LENGTH_STARTS: [usize; 8] = [10, 10, 0, 1, 2, 3, 6, 9];
+const FIVES: Range<usize> = 3..6;
+const SIXES: Range<usize> = 6..9;
+
+// a counting sort, so the length of every pattern is only computed once;
+// the lengths are checked when the input is parsed
+fn by_length(input: &[Pattern]) -> [Pattern; 10] {
+    let mut sorted: [Pattern; 10] = Default::default();
+    let mut next = LENGTH_STARTS;
+    for &pattern in input {
+        let slot = &mut next[pattern.len()];
+        sorted[*slot] = pattern;
+        *slot += 1;
+    }
+    sorted
+}
+
+// none when the patterns contradict each other
+fn associate_numbers(input: &[Pattern]) -> Option<[Pattern; 10]> {
+    let sorted = by_length(input);
+    let (fives, sixes) = (&sorted[FIVES], &sorted[SIXES]);
+    let mut out: [Pattern; 10] = Default::default();
 
     // "one" is the only 2-length pattern
-    out[1] = signal::for_length(input, 2);
+    out[1] = sorted[0];
     // "four" is the only 4-length pattern
-    out[4] = signal::for_length(input, 4);
+    out[4] = sorted[2];
     // "seven" is the only 3-length pattern
-    out[7] = signal::for_length(input, 3);
+    out[7] = sorted[1];
     // "eight" is the only 7-length pattern
-    out[8] = signal::for_length(input, 7);
+    out[8] = sorted[9];
     // "three" is the intersection of any pair of 5-length patterns (2, 3 or 5), merged with a 1
-    out[3] = three(input, &out[1]);
+    out[3] = three(fives, out[1]);
     // "six" is a 6-length pattern which does not include the 1 pattern
-    out[6] = six(input, &out[1]);
+    out[6] = six(sixes, out[1])?;
     // "nine" is a 6-length pattern which includes 1 and 3 patterns
-    out[9] = nine(input, &out[1], &out[3]);
+    out[9] = nine(sixes, out[1], out[3])?;
     // "zero" is a 6-length pattern which includes 1 pattern but not 3
-    out[0] = zero(input, &out[1], &out[3]);
+    out[0] = zero(sixes, out[1], out[3])?;
     // "five" is a 5-length pattern which is included in 9 and is not 3
-    out[5] = five(input, &out[3], &out[9]);
+    out[5] = five(fives, out[3], out[9])?;
     // "two" is a 5-length pattern which is neither 3 nor 5
-    out[2] = two(input, &out[3], &out[5]);
+    out[2] = two(fives, out[3], out[5])?;
 
-    out
+    // every pattern is used once
+    let used = out.iter().fold(0u128, |used, p| used | 1 << p.0);
+    (used.count_ones() == 10).then_some(out)
 }
 
-fn find_by<F>(patterns: &Vec<String>, f: F) -> String
+fn find_by<F>(patterns: &[Pattern], f: F) -> Option<Pattern>
 where
-    F: Fn(&&String) -> bool,
+    F: Fn(&&Pattern) -> bool,
 {
-    let result = patterns.iter().find(f).unwrap();
-    signal::find(&result, patterns)
+    patterns.iter().find(f).copied()
 }
 
-fn three(patterns: &Vec<String>, one: &str) -> String {
+// the helpers below take the patterns of the right length only
+
+fn three(fives: &[Pattern], one: Pattern) -> Pattern {
     // doesn't really matter which ones we select, since all three share the three middle leds
-    let five_length_signals: Vec<&String> = patterns.iter().filter(|pat| pat.len() == 5).collect();
-
-    let three = signal::union(
-        one,
-        &signal::intersection(
-            five_length_signals[0].as_str(),
-            five_length_signals[1].as_str(),
-        ),
-    );
-    signal::find(&three, patterns)
+    one.union(fives[0].intersection(fives[1]))
 }
 
-fn six(patterns: &Vec<String>, one: &str) -> String {
-    find_by(patterns, |pat| {
-        pat.len() == 6 && !signal::contains(pat, one)
-    })
+fn six(sixes: &[Pattern], one: Pattern) -> Option<Pattern> {
+    find_by(sixes, |pat| !pat.contains(one))
 }
 
-fn nine(patterns: &Vec<String>, one: &str, three: &str) -> String {
-    find_by(patterns, |pat| {
-        pat.len() == 6 && signal::contains(pat, one) && signal::contains(pat, three)
-    })
+fn nine(sixes: &[Pattern], one: Pattern, three: Pattern) -> Option<Pattern> {
+    find_by(sixes, |pat| pat.contains(one) && pat.contains(three))
 }
 
-fn zero(patterns: &Vec<String>, one: &str, three: &str) -> String {
-    find_by(patterns, |pat| {
-        pat.len() == 6 && signal::contains(pat, one) && !signal::contains(pat, three)
-    })
+fn zero(sixes: &[Pattern], one: Pattern, three: Pattern) -> Option<Pattern> {
+    find_by(sixes, |pat| pat.contains(one) && !pat.contains(three))
 }
 
-fn five(patterns: &Vec<String>, three: &str, nine: &str) -> String {
-    find_by(patterns, |pat| {
-        pat.len() == 5 && signal::contains(nine, pat) && !signal::matches(pat, three)
-    })
+fn five(fives: &[Pattern], three: Pattern, nine: Pattern) -> Option<Pattern> {
+    find_by(fives, |&&pat| nine.contains(pat) && pat != three)
 }
 
-fn two(patterns: &Vec<String>, three: &str, five: &str) -> String {
-    find_by(patterns, |pat| {
-        pat.len() == 5 && !signal::matches(pat, &three) && !signal::matches(pat, &five)
-    })
+fn two(fives: &[Pattern], three: Pattern, five: Pattern) -> Option<Pattern> {
+    find_by(fives, |&&pat| pat != three && pat != five)
 }
 
 #[cfg(test)]
@@ -102,81 +108,108 @@ mod tests {
     use super::*;
     use crate::Input;
 
+    fn pattern(s: &str) -> Pattern {
+        s.parse().unwrap()
+    }
+
+    fn sorted() -> [Pattern; 10] {
+        by_length(&example().patterns)
+    }
+
+    fn example() -> Input {
+        "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_by_length() {
+        let sorted = sorted();
+        assert_eq!(sorted[0], pattern("ab"));
+        assert_eq!(sorted[9], pattern("acedgfb"));
+        assert!(sorted[FIVES].iter().all(|p| p.len() == 5));
+        assert!(sorted[SIXES].iter().all(|p| p.len() == 6));
+    }
+
     #[test]
     fn test_three() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
-        let result = three(&input.patterns, "ab");
-        assert_eq!(result, "fbcad")
+        let sorted = sorted();
+        let result = three(&sorted[FIVES], pattern("ab"));
+        assert_eq!(result, pattern("fbcad"))
     }
 
     #[test]
     fn test_nine() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
-        let result = nine(&input.patterns, "ab", "fbcad");
-        assert_eq!(result, "cefabd")
+        let sorted = sorted();
+        let result = nine(&sorted[SIXES], pattern("ab"), pattern("fbcad"));
+        assert_eq!(result, Some(pattern("cefabd")))
     }
 
     #[test]
     fn test_five() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
-        let result = five(&input.patterns, "fbcad", "cefabd");
-        assert_eq!(result, "cdfbe")
+        let sorted = sorted();
+        let result = five(&sorted[FIVES], pattern("fbcad"), pattern("cefabd"));
+        assert_eq!(result, Some(pattern("cdfbe")))
     }
 
     #[test]
     fn test_six() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
-        let result = six(&input.patterns, "ab");
-        assert_eq!(result, "cdfgeb")
+        let sorted = sorted();
+        let result = six(&sorted[SIXES], pattern("ab"));
+        assert_eq!(result, Some(pattern("cdfgeb")))
     }
 
     #[test]
     fn test_zero() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
-        let result = zero(&input.patterns, "ab", "fbcad");
-        assert_eq!(result, "cagedb")
+        let sorted = sorted();
+        let result = zero(&sorted[SIXES], pattern("ab"), pattern("fbcad"));
+        assert_eq!(result, Some(pattern("cagedb")))
     }
 
     #[test]
     fn test_two() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
-        let result = two(&input.patterns, "fbcad", "cdfbe");
-        assert_eq!(result, "gcdfa")
+        let sorted = sorted();
+        let result = two(&sorted[FIVES], pattern("fbcad"), pattern("cdfbe"));
+        assert_eq!(result, Some(pattern("gcdfa")))
     }
 
     #[test]
     fn test_associate_numbers() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
+        let input = example();
         let result = associate_numbers(&input.patterns);
         assert_eq!(
-            result,
+            result.unwrap(),
             [
                 "cagedb", "ab", "gcdfa", "fbcad", "eafb", "cdfbe", "cdfgeb", "dab", "acedgfb",
                 "cefabd"
             ]
+            .map(pattern)
         );
     }
 
     #[test]
     fn test_digits() {
-        let input = Input::from(
-            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
-        );
+        let input = example();
         let result = digits(&input);
-        assert_eq!(result, 5353)
+        assert_eq!(result, Ok(5353))
+    }
+
+    #[test]
+    fn test_no_wiring() {
+        // "six" is replaced by a 6-length pattern that includes "one"
+        let input: Input =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgab eafb cagedb ab | cdfeb fcadb cdfeb cdbaf"
+                .parse()
+                .unwrap();
+        assert_eq!(digits(&input), Err(InputError::NoWiring));
+    }
+
+    #[test]
+    fn test_unknown_digit() {
+        let input: Input =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fg cdfeb cdbaf"
+                .parse()
+                .unwrap();
+        assert_eq!(digits(&input), Err(InputError::UnknownDigit(pattern("fg"))));
     }
 }
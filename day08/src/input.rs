@@ -1,13 +1,163 @@
+use crate::signal::{InvalidSegment, Pattern};
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
 pub struct Input {
-    pub patterns: Vec<String>,
-    pub digits: Vec<String>,
+    pub patterns: Vec<Pattern>,
+    pub digits: Vec<Pattern>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InputError {
+    MissingSeparator,
+    InvalidPattern(InvalidSegment),
+    PatternCount(usize),
+    // the ten digits have one pattern of 2, 3, 4 and 7 segments and three of 5 and 6
+    LengthCount {
+        length: usize,
+        expected: usize,
+        found: usize,
+    },
+    // the patterns have the right lengths but no wiring gives them all
+    NoWiring,
+    // an output digit that is none of the ten patterns
+    UnknownDigit(Pattern),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            InputError::MissingSeparator => write!(f, "expected patterns | digits"),
+            InputError::InvalidPattern(e) => write!(f, "{}", e),
+            InputError::PatternCount(found) => {
+                write!(f, "expected 10 patterns before the |, found {}", found)
+            }
+            InputError::LengthCount {
+                length,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} patterns of {} segments, found {}",
+                expected, length, found
+            ),
+            InputError::NoWiring => write!(f, "no wiring of the segments gives these patterns"),
+            InputError::UnknownDigit(digit) => {
+                write!(f, "output digit {} is none of the patterns", digit)
+            }
+        }
+    }
+}
+
+impl From<InvalidSegment> for InputError {
+    fn from(e: InvalidSegment) -> InputError {
+        InputError::InvalidPattern(e)
+    }
+}
+
+fn patterns(s: &str) -> Result<Vec<Pattern>, InvalidSegment> {
+    s.split_whitespace().map(str::parse).collect()
+}
+
+// the number of patterns with 0 to 7 segments
+const LENGTHS: [usize; 8] = [0, 0, 1, 1, 1, 3, 3, 1];
+
+fn check_lengths(patterns: &[Pattern]) -> Result<(), InputError> {
+    if patterns.len() != 10 {
+        return Err(InputError::PatternCount(patterns.len()));
+    }
+
+    let mut found = [0; 8];
+    patterns.iter().for_each(|p| found[p.len()] += 1);
+    match (0..8).find(|&length| found[length] != LENGTHS[length]) {
+        Some(length) => Err(InputError::LengthCount {
+            length,
+            expected: LENGTHS[length],
+            found: found[length],
+        }),
+        None => Ok(()),
+    }
+}
+
+impl FromStr for Input {
+    type Err = InputError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (patterns_part, digits_part) =
+            s.split_once(" | ").ok_or(InputError::MissingSeparator)?;
+        let patterns_list = patterns(patterns_part)?;
+        check_lengths(&patterns_list)?;
+        Ok(Input {
+            patterns: patterns_list,
+            digits: patterns(digits_part)?,
+        })
+    }
 }
 
-impl From<&str> for Input {
-    fn from(s: &str) -> Self {
-        let line: Vec<&str> = s.split(" | ").collect();
-        let patterns = line[0].split_whitespace().map(|s| s.into()).collect();
-        let digits = line[1].split_whitespace().map(|s| s.into()).collect();
-        Input { patterns, digits }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let input: Input =
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | ba gfedcba"
+                .parse()
+                .unwrap();
+        assert_eq!(input.patterns.len(), 10);
+        assert_eq!(input.patterns[9], Pattern(0b11));
+        assert_eq!(input.digits, vec![Pattern(0b11), Pattern(0b1111111)]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!(
+            "ab cde ba".parse::<Input>(),
+            Err(InputError::MissingSeparator)
+        ));
+        assert!(matches!(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | bx".parse::<Input>(),
+            Err(InputError::InvalidPattern(InvalidSegment('x')))
+        ));
+    }
+
+    #[test]
+    fn test_pattern_count() {
+        assert_eq!(
+            "ab cde | ba".parse::<Input>().err(),
+            Some(InputError::PatternCount(2))
+        );
+        assert_eq!(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab ab | ba"
+                .parse::<Input>()
+                .err(),
+            Some(InputError::PatternCount(11))
+        );
+    }
+
+    #[test]
+    fn test_length_count() {
+        // a single segment instead of the 2 of "one"
+        assert_eq!(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb a | ba"
+                .parse::<Input>()
+                .err(),
+            Some(InputError::LengthCount {
+                length: 1,
+                expected: 0,
+                found: 1
+            })
+        );
+        // four 5-length patterns and no "four"
+        assert_eq!(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafbc cagedb ab | ba"
+                .parse::<Input>()
+                .err(),
+            Some(InputError::LengthCount {
+                length: 4,
+                expected: 1,
+                found: 0
+            })
+        );
     }
 }
@@ -2,14 +2,21 @@ mod decoder;
 mod input;
 mod signal;
 
-use input::Input;
+use input::{Input, InputError};
 
 fn load_input() -> Vec<Input> {
     let input = std::fs::read_to_string("input").expect("Unable to read file");
-    input.lines().map(Input::from).collect::<Vec<Input>>()
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            line.parse()
+                .unwrap_or_else(|e| panic!("Invalid entry at line {}: {}", idx + 1, e))
+        })
+        .collect::<Vec<Input>>()
 }
 
-fn part_1(input: &Vec<Input>) -> i64 {
+fn part_1(input: &[Input]) -> i64 {
     input
         .iter()
         .flat_map(|i| i.digits.iter())
@@ -17,14 +24,17 @@ fn part_1(input: &Vec<Input>) -> i64 {
         .count() as i64
 }
 
-fn part_2(input: &Vec<Input>) -> i64 {
-    input.iter().map(|ipt| decoder::digits(ipt)).sum::<i64>()
+fn part_2(input: &[Input]) -> Result<i64, InputError> {
+    input.iter().map(decoder::digits).sum()
 }
 
 fn main() {
     let input = load_input();
     println!("{}", part_1(&input));
-    println!("{}", part_2(&input));
+    match part_2(&input) {
+        Ok(sum) => println!("{}", sum),
+        Err(e) => panic!("Unable to decode the entries: {}", e),
+    }
 }
 
 #[cfg(test)]
@@ -44,7 +54,7 @@ mod tests {
             "bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef",
             "egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb",
             "gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce"
-        ].into_iter().map(Input::from).collect();
+        ].into_iter().map(|l| l.parse().unwrap()).collect::<Vec<Input>>();
 
         assert_eq!(part_1(&input), 26);
     }
@@ -62,8 +72,8 @@ mod tests {
             "bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef",
             "egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb",
             "gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce"
-        ].into_iter().map(Input::from).collect();
+        ].into_iter().map(|l| l.parse().unwrap()).collect::<Vec<Input>>();
 
-        assert_eq!(part_2(&input), 61229);
+        assert_eq!(part_2(&input), Ok(61229));
     }
 }
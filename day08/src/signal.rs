@@ -1,68 +1,107 @@
-use std::collections::HashSet;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
 
-pub fn intersection(a: &str, b: &str) -> String {
-    let a_chars = a.chars().collect::<HashSet<_>>();
-    let b_chars = b.chars().collect::<HashSet<_>>();
-    a_chars.intersection(&b_chars).collect()
-}
+// the segments a..g lit by a pattern, as bits 0..6
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Pattern(pub u8);
 
-pub fn union(a: &str, b: &str) -> String {
-    let a_chars = a.chars().collect::<HashSet<_>>();
-    let b_chars = b.chars().collect::<HashSet<_>>();
-    a_chars.union(&b_chars).collect()
-}
+#[derive(Debug, PartialEq)]
+pub struct InvalidSegment(pub char);
 
-pub fn contains(container: &str, content: &str) -> bool {
-    content.chars().all(|c| container.chars().any(|x| x == c))
+impl Display for InvalidSegment {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "'{}' is not a segment, expected a..g", self.0)
+    }
 }
 
-pub fn matches(a: &str, b: &str) -> bool {
-    a.chars().collect::<HashSet<_>>() == b.chars().collect::<HashSet<_>>()
+impl FromStr for Pattern {
+    type Err = InvalidSegment;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars().try_fold(Pattern(0), |pattern, c| match c {
+            'a'..='g' => Ok(Pattern(pattern.0 | 1 << (c as u8 - b'a'))),
+            _ => Err(InvalidSegment(c)),
+        })
+    }
 }
 
-pub fn find<'a>(needle: &str, stack: &Vec<String>) -> String {
-    stack
-        .iter()
-        .find(|&x| matches(x.as_str(), needle))
-        .unwrap()
-        .clone()
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        (0..7)
+            .filter(|bit| self.0 & 1 << bit != 0)
+            .try_for_each(|bit| write!(f, "{}", (b'a' + bit) as char))
+    }
 }
 
-pub fn for_length(patterns: &Vec<String>, length: usize) -> String {
-    patterns
-        .iter()
-        .find(|&x| x.len() == length)
-        .unwrap()
-        .clone()
+impl Pattern {
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn intersection(self, other: Pattern) -> Pattern {
+        Pattern(self.0 & other.0)
+    }
+
+    pub fn union(self, other: Pattern) -> Pattern {
+        Pattern(self.0 | other.0)
+    }
+
+    pub fn contains(self, content: Pattern) -> bool {
+        self.0 & content.0 == content.0
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn pattern(s: &str) -> Pattern {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(pattern("abc"), Pattern(0b111));
+        assert_eq!(pattern("gb"), Pattern(0b1000010));
+        assert_eq!(pattern("acedgfb").len(), 7);
+    }
+
+    #[test]
+    fn test_parse_invalid_segment() {
+        assert_eq!("abh".parse::<Pattern>(), Err(InvalidSegment('h')));
+        assert_eq!("aB".parse::<Pattern>(), Err(InvalidSegment('B')));
+        assert_eq!("a b".parse::<Pattern>(), Err(InvalidSegment(' ')));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(pattern("gecf").to_string(), "cefg");
+    }
+
     #[test]
     fn test_union() {
-        let result = union("abc", "cde");
+        let result = pattern("abc").union(pattern("cde"));
 
-        assert!(matches(&result, "abcde"))
+        assert_eq!(result, pattern("abcde"))
     }
 
     #[test]
     fn test_intersection() {
-        let result = intersection("dbca", "cde");
+        let result = pattern("dbca").intersection(pattern("cde"));
 
-        assert!(matches(&result, "cd"))
+        assert_eq!(result, pattern("cd"))
     }
 
     #[test]
     fn test_matches() {
-        assert!(matches("abcde", "edbca"));
-        assert!(!matches("abcde", "abcdf"));
-        assert!(!matches("ab", "abc"));
+        assert_eq!(pattern("abcde"), pattern("edbca"));
+        assert_ne!(pattern("abcde"), pattern("abcdf"));
+        assert_ne!(pattern("ab"), pattern("abc"));
     }
 
     #[test]
     fn test_contains() {
-        assert!(contains("abc", "cb"));
+        assert!(pattern("abc").contains(pattern("cb")));
+        assert!(!pattern("cb").contains(pattern("abc")));
     }
 }